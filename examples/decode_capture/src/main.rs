@@ -0,0 +1,63 @@
+//! Decode a capture of `NETLINK_ROUTE` traffic, like one taken with:
+//!
+//! ```sh
+//! ip link add nlmon0 type nlmon
+//! ip link set nlmon0 up
+//! tcpdump -i nlmon0 -w netlink.pcap
+//! ```
+//!
+//! Captures in the pcapng format, which Wireshark and `dumpcap` write by
+//! default, need to be converted first:
+//!
+//! ```sh
+//! editcap -F pcap netlink.pcapng netlink.pcap
+//! ```
+//!
+//! Usage: `decode_capture <capture.pcap> [--json]`
+
+use netlink::route::decode_message;
+use netlink::PcapReader;
+use std::error::Error;
+use std::time::UNIX_EPOCH;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    let Some(path) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        eprintln!("usage: decode_capture <capture.pcap> [--json]");
+        std::process::exit(1);
+    };
+
+    for packet in PcapReader::open(path)? {
+        let packet = packet?;
+        let time = packet.time.duration_since(UNIX_EPOCH)?.as_secs_f64();
+
+        // Only NETLINK_ROUTE messages can be decoded
+        if packet.protocol != 0 {
+            continue;
+        }
+
+        for msg in packet.messages()? {
+            let header = &msg.header;
+            let decoded = decode_message(&msg);
+
+            if json {
+                let value = serde_json::json!({
+                    "time": time,
+                    "type": header.typ(),
+                    "flags": header.flags(),
+                    "seq": header.seq(),
+                    "pid": header.pid(),
+                    "message": decoded.as_ref().ok(),
+                    "error": decoded.as_ref().err().map(ToString::to_string),
+                });
+                println!("{value}");
+                continue;
+            }
+
+            println!("{time:.6} {}", msg.pretty());
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use netlink::route::{RouteMessage, RouteMessageType, AF_INET};
-use netlink::{Flag, NetlinkMessage, NetlinkStream};
+use netlink::route::route::{RouteMessage, RouteMessageType};
+use netlink::route::AF_INET;
+use netlink::{Flags, GetFlags, NetlinkMessage, NetlinkStream};
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -13,17 +14,14 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let msg = NetlinkMessage::builder()
         .typ(RouteMessageType::GetRoute)
-        .flags(Flag::Request | Flag::Dump)
+        .flags(Flags::REQUEST | GetFlags::DUMP)
         .append(rthdr)?
         .build();
 
     conn.send(msg)?;
 
     while let Ok(Some(msg)) = conn.recv() {
-        log::info!(
-            "Received a Netlink message with a {} byte payload",
-            msg.payload.len()
-        );
+        log::info!("{}", msg.pretty());
     }
 
     Ok(())
@@ -0,0 +1,77 @@
+use super::{aligned_size, aligned_size_of, serialize_aligned, SliceReader};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// Set on attributes that contain other attributes
+const NLA_F_NESTED: u16 = 0x8000;
+
+/// Set on attributes whose payload is in network byte order
+const NLA_F_NET_BYTEORDER: u16 = 0x4000;
+
+/// Masks out the flags that the kernel may set on the attribute type
+const NLA_TYPE_MASK: u16 = !(NLA_F_NESTED | NLA_F_NET_BYTEORDER);
+
+// Header of every Netlink attribute. See `struct nlattr` in
+// https://github.com/torvalds/linux/blob/master/include/uapi/linux/netlink.h
+#[repr(C)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+struct AttrHeader {
+    len: u16,
+    typ: u16,
+}
+
+impl<'a> SliceReader<'a> {
+    // Read the next attribute. The length in the attribute header does not
+    // include the padding after the value, so it is skipped separately.
+    pub(crate) fn read_attribute(&mut self) -> Result<(u16, &'a [u8])> {
+        let hdr = self.read::<AttrHeader>()?;
+
+        let value_len = (hdr.len as usize)
+            .checked_sub(aligned_size_of::<AttrHeader>())
+            .ok_or(crate::Error::ErrUnexpectedEof)?;
+        let value = self.take(value_len)?;
+
+        let padding_len = aligned_size(value_len) - value_len;
+        self.take(padding_len.min(self.remaining()))?;
+
+        Ok((hdr.typ & NLA_TYPE_MASK, value))
+    }
+}
+
+// Serialize an attribute header and value, padded to a 4 byte alignment.
+pub(crate) fn serialize_attribute(typ: u16, value: &[u8]) -> Result<Vec<u8>> {
+    let len = aligned_size_of::<AttrHeader>() + value.len();
+    let hdr = AttrHeader {
+        len: len
+            .try_into()
+            .map_err(|_| crate::Error::ErrValueConversion)?,
+        typ,
+    };
+
+    let mut bytes = serialize_aligned(hdr)?;
+    bytes.extend_from_slice(value);
+    bytes.resize(aligned_size(len), 0);
+    Ok(bytes)
+}
+
+// Serialize an attribute that contains other attributes, which are already
+// serialized into `value`.
+pub(crate) fn serialize_nested_attribute(typ: u16, value: &[u8]) -> Result<Vec<u8>> {
+    serialize_attribute(typ | NLA_F_NESTED, value)
+}
+
+// Append an attribute to attributes that are being nested in another one.
+// The length saturates instead of failing: a value that doesn't fit can't fit
+// in the outer attribute either, and serializing that one fails.
+pub(crate) fn push_attribute(bytes: &mut Vec<u8>, typ: u16, value: &[u8]) {
+    let len = aligned_size_of::<AttrHeader>() + value.len();
+    bytes.extend_from_slice(&u16::try_from(len).unwrap_or(u16::MAX).to_le_bytes());
+    bytes.extend_from_slice(&typ.to_le_bytes());
+    bytes.extend_from_slice(value);
+    bytes.resize(bytes.len() + aligned_size(len) - len, 0);
+}
+
+// Like `push_attribute`, for an attribute that contains other attributes
+pub(crate) fn push_nested_attribute(bytes: &mut Vec<u8>, typ: u16, value: &[u8]) {
+    push_attribute(bytes, typ | NLA_F_NESTED, value);
+}
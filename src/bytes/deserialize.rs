@@ -1,16 +1,23 @@
 use crate::{Error, Result};
 use bincode::deserialize;
+use serde::de::DeserializeOwned;
 use std::mem::size_of;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-pub(crate) fn deserialize_i8(payload: &[u8]) -> Result<i8> {
+pub(crate) fn deserialize_u8(payload: &[u8]) -> Result<u8> {
     let bytes: [u8; 1] = payload.try_into().map_err(|_| Error::ErrUnexpectedEof)?;
-    Ok(i8::from_le_bytes(bytes))
+    Ok(u8::from_le_bytes(bytes))
 }
 
-pub(crate) fn deserialize_i16(payload: &[u8]) -> Result<i16> {
+pub(crate) fn deserialize_u16(payload: &[u8]) -> Result<u16> {
     let bytes: [u8; 2] = payload.try_into().map_err(|_| Error::ErrUnexpectedEof)?;
-    Ok(i16::from_le_bytes(bytes))
+    Ok(u16::from_le_bytes(bytes))
+}
+
+// Ports and tunnel keys are in network byte order, unlike other integers
+pub(crate) fn deserialize_u16_be(payload: &[u8]) -> Result<u16> {
+    let bytes: [u8; 2] = payload.try_into().map_err(|_| Error::ErrUnexpectedEof)?;
+    Ok(u16::from_be_bytes(bytes))
 }
 
 pub(crate) fn deserialize_i32(payload: &[u8]) -> Result<i32> {
@@ -23,6 +30,16 @@ pub(crate) fn deserialize_u32(payload: &[u8]) -> Result<u32> {
     Ok(u32::from_le_bytes(bytes))
 }
 
+pub(crate) fn deserialize_u64(payload: &[u8]) -> Result<u64> {
+    let bytes: [u8; 8] = payload.try_into().map_err(|_| Error::ErrUnexpectedEof)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn deserialize_u64_be(payload: &[u8]) -> Result<u64> {
+    let bytes: [u8; 8] = payload.try_into().map_err(|_| Error::ErrUnexpectedEof)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
 pub(crate) fn deserialize_ascii(payload: &[u8]) -> String {
     String::from_utf8_lossy(payload)
         .trim_matches(char::from(0))
@@ -40,3 +57,9 @@ pub(crate) fn deserialize_ip_addr(payload: &[u8]) -> Result<IpAddr> {
             .map_err(Error::ErrDeserialize)
     }
 }
+
+// Attribute types are deserialized from their raw value so that attributes
+// added by newer kernels can be skipped, rather than failing the whole message.
+pub(crate) fn deserialize_repr<T: DeserializeOwned>(value: u16) -> Option<T> {
+    deserialize(&value.to_le_bytes()).ok()
+}
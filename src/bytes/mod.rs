@@ -4,5 +4,11 @@ pub(crate) use reader::*;
 pub(crate) mod align;
 pub(crate) use align::*;
 
+pub(crate) mod attr;
+pub(crate) use attr::*;
+
 pub(crate) mod deserialize;
 pub(crate) use deserialize::*;
+
+pub(crate) mod serialize;
+pub(crate) use serialize::*;
@@ -13,7 +13,7 @@ impl<'a> SliceReader<'a> {
         Self { slice, cursor: 0 }
     }
 
-    pub(crate) fn take(&mut self, len: usize) -> Result<&[u8]> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8]> {
         if self.cursor + len > self.slice.len() {
             return Err(Error::ErrUnexpectedEof);
         }
@@ -29,6 +29,10 @@ impl<'a> SliceReader<'a> {
         Ok(val)
     }
 
+    pub(crate) fn remaining(&self) -> usize {
+        self.slice.len().saturating_sub(self.cursor)
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.cursor >= self.slice.len()
     }
@@ -0,0 +1,474 @@
+use crate::bytes::{aligned_size, aligned_size_of, serialize_aligned, SliceReader};
+use crate::flags::Flags;
+use crate::stats::Stats;
+use crate::types::{MessageType, NetlinkHeader, NetlinkMessage};
+use crate::{Error, Result};
+use nix::errno::Errno;
+use std::collections::VecDeque;
+
+/// Something that happened while decoding the datagrams received from the
+/// kernel. See [`NetlinkCodec::poll_event`].
+#[derive(PartialEq, Clone, Debug)]
+pub enum NetlinkEvent {
+    /// A message was received in response to the last request.
+    Message(NetlinkMessage),
+    /// A message with type [`MessageType::Done`] terminated the current
+    /// multipart message sequence.
+    Done,
+    /// A message with type [`MessageType::Done`] terminated the current
+    /// multipart message sequence with a negative status. The kernel failed
+    /// part way through the dump, so the messages received so far are
+    /// incomplete.
+    DumpFailed(Errno),
+}
+
+/// How much of the response to the last request is left to read.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Response {
+    Complete,
+    /// More messages are expected
+    Pending,
+    /// More messages are expected, ending with the ACK the request asked for
+    PendingAck,
+}
+
+/// Sans-IO implementation of the Netlink protocol.
+///
+/// This owns the framing, sequence numbering and multipart state that is
+/// required to talk to the kernel, but never touches a socket. Requests are
+/// turned into bytes with [`NetlinkCodec::encode`], and the datagrams read back
+/// are fed into [`NetlinkCodec::decode`], which queues [`NetlinkEvent`]s. This
+/// makes it possible to drive the protocol from any event loop, or from
+/// datagrams that were captured earlier.
+///
+/// [`NetlinkStream`](crate::NetlinkStream) is a thin wrapper around this codec
+/// and a Netlink socket.
+///
+/// For example:
+///
+/// ```rust
+/// use netlink::route::route::{RouteMessage, RouteMessageType};
+/// use netlink::route::AF_INET;
+/// use netlink::{Flags, GetFlags, NetlinkCodec, NetlinkEvent, NetlinkMessage};
+///
+/// # fn main() -> netlink::Result<()> {
+/// let mut codec = NetlinkCodec::new(0);
+///
+/// let rthdr = RouteMessage::builder().family(AF_INET).build()?;
+/// let msg = NetlinkMessage::builder()
+///     .typ(RouteMessageType::GetRoute)
+///     .flags(Flags::REQUEST | GetFlags::DUMP)
+///     .append(rthdr)?
+///     .build();
+///
+/// // Bytes ready to be written to the socket.
+/// let request = codec.encode(msg)?;
+/// assert_eq!(request.len(), 28);
+///
+/// // An empty NLMSG_DONE message, as it would be read from the socket.
+/// let done = [
+///     16, 0, 0, 0, 3, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+/// ];
+/// codec.decode(&done)?;
+///
+/// assert_eq!(codec.poll_event(), Some(NetlinkEvent::Done));
+/// assert!(!codec.has_remaining_reads());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NetlinkCodec {
+    pid: u32,
+    seq: u32,
+    response: Response,
+    dump_interrupted: bool,
+    discard_stale: bool,
+    events: VecDeque<NetlinkEvent>,
+    stats: Stats,
+}
+
+impl NetlinkCodec {
+    /// Create a codec that stamps outgoing messages with the given port ID.
+    #[must_use]
+    pub fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            seq: 0,
+            response: Response::Complete,
+            dump_interrupted: false,
+            discard_stale: false,
+            events: VecDeque::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// The port ID written into the header of every outgoing message.
+    #[must_use]
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// The sequence number that will be assigned to the next message.
+    #[must_use]
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// Whether more datagrams must be read before the response to the last
+    /// request is complete.
+    #[must_use]
+    pub fn has_remaining_reads(&self) -> bool {
+        self.response != Response::Complete
+    }
+
+    /// Counters of the messages that were encoded and decoded.
+    #[must_use]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub(crate) fn stats_mut(&mut self) -> &mut Stats {
+        &mut self.stats
+    }
+
+    /// Serialize a message into the bytes that must be sent to the kernel.
+    ///
+    /// This fills in the length, port ID and sequence number of the header.
+    /// Any events that were not polled from the previous request are
+    /// discarded. If the message has [`Flags::ACK`], the response is only
+    /// complete once the ACK or an error arrives, or the dump is done.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the message is too large to be
+    /// described by a Netlink header.
+    pub fn encode(&mut self, msg: NetlinkMessage) -> Result<Vec<u8>> {
+        let bytes = serialize_message(
+            msg.header.typ,
+            msg.header.flags,
+            self.seq,
+            self.pid,
+            msg.payload,
+        )?;
+
+        self.seq = self.seq.wrapping_add(1);
+        self.response = if Flags::from_bits_truncate(msg.header.flags).contains(Flags::ACK) {
+            Response::PendingAck
+        } else {
+            Response::Pending
+        };
+        self.dump_interrupted = false;
+        self.events.clear();
+
+        self.stats.messages_sent += 1;
+        self.stats.bytes_sent += bytes.len() as u64;
+        Ok(bytes)
+    }
+
+    /// Split a datagram received from the kernel into messages and queue the
+    /// resulting events. They can be retrieved with
+    /// [`NetlinkCodec::poll_event`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the datagram does not contain
+    /// well-formed Netlink messages.
+    pub fn decode(&mut self, datagram: &[u8]) -> Result<()> {
+        self.stats.datagrams_received += 1;
+        self.stats.bytes_received += datagram.len() as u64;
+
+        let messages = split_messages(datagram).map_err(|err| {
+            if matches!(err, Error::ErrUnexpectedEof) {
+                self.stats.truncations += 1;
+            }
+            err
+        })?;
+
+        for (hdr, payload) in messages {
+            self.stats.messages_received += 1;
+
+            // The rest of an abandoned response may still arrive, and must
+            // not be mistaken for the response to the current request
+            if self.discard_stale && hdr.seq != self.seq.wrapping_sub(1) {
+                self.stats.stale_messages += 1;
+                continue;
+            }
+
+            if hdr.has_flags(Flags::DUMP_INTR) {
+                self.dump_interrupted = true;
+            }
+
+            if hdr.has_type(MessageType::Noop) {
+                continue;
+            }
+
+            if hdr.has_type(MessageType::Overrun) {
+                self.stats.overruns += 1;
+            }
+
+            if hdr.has_type(MessageType::Done) {
+                self.stats.dumps_completed += 1;
+                if self.dump_interrupted {
+                    self.stats.dumps_interrupted += 1;
+                }
+
+                // The status of the dump is negative if it failed part way
+                let status = SliceReader::new(&payload).read::<i32>().unwrap_or(0);
+                let event = if status < 0 {
+                    self.stats.record_error(status);
+                    NetlinkEvent::DumpFailed(Errno::from_i32(status.wrapping_neg()))
+                } else {
+                    NetlinkEvent::Done
+                };
+
+                // Dumps are never acknowledged, even if the request asked
+                self.response = Response::Complete;
+                self.events.push_back(event);
+                continue;
+            }
+
+            let is_error = hdr.has_type(MessageType::Error);
+            if is_error {
+                if let Ok(code) = SliceReader::new(&payload).read::<i32>() {
+                    self.stats.record_error(code);
+                }
+            }
+
+            // Anything that isn't part of a multipart message is the only
+            // response to the request, except for the ACK or error that
+            // follows it if one was requested.
+            let is_last = is_error || self.response == Response::Pending;
+            if !hdr.has_flags(Flags::MULTI) && is_last {
+                self.response = Response::Complete;
+            }
+
+            let msg = NetlinkMessage::new(hdr.into_descriptor(), payload);
+            self.events.push_back(NetlinkEvent::Message(msg));
+        }
+
+        Ok(())
+    }
+
+    /// Give up on the rest of the response to the last request, like after
+    /// the transport failed part way through it. From then on, messages that
+    /// don't answer the last request sent are discarded, so that the
+    /// remaining messages of the abandoned response aren't mistaken for the
+    /// response to the next one.
+    pub fn abandon(&mut self) {
+        if self.response != Response::Complete {
+            self.discard_stale = true;
+        }
+        self.response = Response::Complete;
+        self.events.clear();
+    }
+
+    /// Take the next event produced by [`NetlinkCodec::decode`], if there is
+    /// one.
+    pub fn poll_event(&mut self) -> Option<NetlinkEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// Serialize a complete Netlink message, including the header.
+pub(crate) fn serialize_message(
+    typ: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+    mut payload: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let len = payload.len() + aligned_size_of::<NetlinkHeader>();
+    let header = NetlinkHeader {
+        len: len.try_into().map_err(|_| Error::ErrValueConversion)?,
+        typ,
+        flags,
+        seq,
+        pid,
+    };
+
+    let mut bytes = serialize_aligned(header)?;
+    bytes.append(&mut payload);
+    bytes.resize(aligned_size(bytes.len()), 0);
+    Ok(bytes)
+}
+
+/// Split a datagram into the headers and payloads of the Netlink messages it
+/// contains.
+pub(crate) fn split_messages(datagram: &[u8]) -> Result<Vec<(NetlinkHeader, Vec<u8>)>> {
+    let mut reader = SliceReader::new(datagram);
+    let mut messages = vec![];
+
+    while !reader.is_empty() {
+        let hdr = reader.read::<NetlinkHeader>()?;
+
+        let payload_len = (hdr.len as usize)
+            .checked_sub(aligned_size_of::<NetlinkHeader>())
+            .ok_or(Error::ErrUnexpectedEof)?;
+        let payload = reader.take(payload_len)?.to_vec();
+
+        // The last message in a datagram is not always padded
+        let padding_len = aligned_size(payload_len) - payload_len;
+        reader.take(padding_len.min(reader.remaining()))?;
+
+        messages.push((hdr, payload));
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flags::GetFlags;
+
+    const RTM_NEWROUTE: u16 = 24;
+    const RTM_GETROUTE: u16 = 26;
+
+    // Encode a request of type RTM_GETROUTE with the given flags
+    fn request(codec: &mut NetlinkCodec, flags: u16) -> Result<()> {
+        let msg = serialize_message(RTM_GETROUTE, flags, 0, 0, vec![0; 12])?;
+        let (hdr, payload) = split_messages(&msg)?.remove(0);
+        codec.encode(NetlinkMessage::new(hdr.into_descriptor(), payload))?;
+        Ok(())
+    }
+
+    fn route(flags: Flags) -> Result<Vec<u8>> {
+        serialize_message(RTM_NEWROUTE, flags.bits(), 0, 0, vec![0; 12])
+    }
+
+    fn ack(code: i32) -> Result<Vec<u8>> {
+        let payload = code.to_le_bytes().to_vec();
+        serialize_message(MessageType::Error.into(), 0, 0, 0, payload)
+    }
+
+    fn done() -> Result<Vec<u8>> {
+        let status = 0i32.to_le_bytes().to_vec();
+        serialize_message(MessageType::Done.into(), Flags::MULTI.bits(), 0, 0, status)
+    }
+
+    fn message_types(codec: &mut NetlinkCodec) -> Vec<u16> {
+        std::iter::from_fn(|| codec.poll_event())
+            .filter_map(|event| match event {
+                NetlinkEvent::Message(msg) => Some(msg.header.typ),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reply_without_ack_completes_the_request() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+        request(&mut codec, Flags::REQUEST.bits())?;
+
+        codec.decode(&route(Flags::empty())?)?;
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(message_types(&mut codec), [RTM_NEWROUTE]);
+        Ok(())
+    }
+
+    #[test]
+    fn acked_request_waits_for_the_ack() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+        request(&mut codec, (Flags::REQUEST | Flags::ACK).bits())?;
+
+        // The reply of a request that isn't a dump comes before the ACK
+        codec.decode(&route(Flags::empty())?)?;
+        assert!(codec.has_remaining_reads());
+
+        codec.decode(&ack(0)?)?;
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(
+            message_types(&mut codec),
+            [RTM_NEWROUTE, MessageType::Error.into()]
+        );
+
+        // The ACK doesn't linger and answer the next request
+        request(&mut codec, (Flags::REQUEST | Flags::ACK).bits())?;
+        assert!(codec.has_remaining_reads());
+        assert_eq!(codec.poll_event(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn error_completes_an_acked_request() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+        request(&mut codec, (Flags::REQUEST | Flags::ACK).bits())?;
+
+        codec.decode(&ack(-(Errno::ESRCH as i32))?)?;
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(codec.stats().error_count(Errno::ESRCH), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn acked_dump_ends_with_done() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+        request(
+            &mut codec,
+            (Flags::REQUEST | GetFlags::DUMP | Flags::ACK).bits(),
+        )?;
+
+        let mut datagram = route(Flags::MULTI)?;
+        datagram.append(&mut route(Flags::MULTI)?);
+        codec.decode(&datagram)?;
+        assert!(codec.has_remaining_reads());
+
+        codec.decode(&done()?)?;
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(message_types(&mut codec), [RTM_NEWROUTE, RTM_NEWROUTE]);
+        Ok(())
+    }
+
+    #[test]
+    fn ack_of_abandoned_request_is_stale() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+        request(&mut codec, (Flags::REQUEST | Flags::ACK).bits())?;
+        codec.decode(&route(Flags::empty())?)?;
+        codec.abandon();
+
+        // The ACK of the first request arrives after the second is sent
+        request(&mut codec, Flags::REQUEST.bits())?;
+        codec.decode(&ack(0)?)?;
+        assert!(codec.has_remaining_reads());
+        assert_eq!(codec.stats().stale_messages, 1);
+
+        // The reply to the second request, with sequence number 1
+        let mut reply = route(Flags::empty())?;
+        reply[8] = 1;
+        codec.decode(&reply)?;
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(message_types(&mut codec), [RTM_NEWROUTE]);
+        Ok(())
+    }
+
+    #[test]
+    fn failed_dump_is_reported() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+
+        let status = (-(Errno::EBUSY as i32)).to_le_bytes().to_vec();
+        let flags = Flags::MULTI.bits();
+        let done = serialize_message(MessageType::Done.into(), flags, 0, 0, status)?;
+        codec.decode(&done)?;
+
+        assert_eq!(
+            codec.poll_event(),
+            Some(NetlinkEvent::DumpFailed(Errno::EBUSY))
+        );
+        assert!(!codec.has_remaining_reads());
+        assert_eq!(codec.stats().error_count(Errno::EBUSY), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn successful_dump_is_done() -> Result<()> {
+        let mut codec = NetlinkCodec::new(0);
+
+        let status = 0i32.to_le_bytes().to_vec();
+        let flags = Flags::MULTI.bits();
+        let done = serialize_message(MessageType::Done.into(), flags, 0, 0, status)?;
+        codec.decode(&done)?;
+
+        assert_eq!(codec.poll_event(), Some(NetlinkEvent::Done));
+        Ok(())
+    }
+}
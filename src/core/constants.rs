@@ -8,6 +8,15 @@ pub const MULTI: u16 = 0x2;
 /// Acknowledgement of success
 pub const ACK: u16 = 0x4;
 
+/// Echo this request
+pub const ECHO: u16 = 0x8;
+
+/// Dump was inconsistent due to a sequence change
+pub const DUMP_INTR: u16 = 0x10;
+
+/// Dump was filtered as requested
+pub const DUMP_FILTERED: u16 = 0x20;
+
 /// Return the complete table instead of a single entry
 pub const ROOT: u16 = 0x100;
 
@@ -18,14 +27,14 @@ pub const MATCH: u16 = 0x200;
 /// capability or an effective UID of 0.
 pub const ATOMIC: u16 = 0x400;
 
-/// Return all entries. Convenience macro, equivalent to
-/// [`MessageFlags::Root`] OR [`MessageFlags::Match`].
+/// Return all entries. Convenience macro, equivalent to [`ROOT`] OR
+/// [`MATCH`].
 pub const DUMP: u16 = ROOT | MATCH;
 
 /// Replace an existing object
 pub const REPLACE: u16 = 0x100;
 
-/// Don't replace if the object already exists (see [`MessageFlags::Replace`])
+/// Don't replace if the object already exists (see [`REPLACE`])
 pub const EXCL: u16 = 0x200;
 
 /// Create object if it doesn't already exist
@@ -33,3 +42,15 @@ pub const CREATE: u16 = 0x400;
 
 /// Add to the end of the object list
 pub const APPEND: u16 = 0x800;
+
+/// Do not delete recursively
+pub const NONREC: u16 = 0x100;
+
+/// Delete multiple objects
+pub const BULK: u16 = 0x200;
+
+/// The request was capped, so the error message doesn't include its payload
+pub const CAPPED: u16 = 0x100;
+
+/// Extended ACK attributes are included in the error message
+pub const ACK_TLVS: u16 = 0x200;
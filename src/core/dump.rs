@@ -0,0 +1,96 @@
+use crate::core::trace::RequestSpan;
+use crate::request::NetlinkRequest;
+use crate::socket::NetlinkSocket;
+use crate::stream::NetlinkStream;
+use crate::transport::Transport;
+use crate::Result;
+use nix::errno::Errno;
+use std::iter::FusedIterator;
+
+/// An iterator over the response to a dump request, which decodes every
+/// message as its datagram arrives. See [`NetlinkStream::dump`].
+///
+/// Each item is decoded on its own, so a message that can't be decoded is
+/// returned as an error and the iteration goes on. An error reported by the
+/// kernel, or a failure to read from the transport, ends it.
+///
+/// Dropping the iterator before the end reads and discards the rest of the
+/// response, so the stream is ready for the next request. If reading fails
+/// part way through, the rest of the response is instead discarded as it
+/// arrives, while reading the response to the next request.
+pub struct Dump<'a, R: NetlinkRequest, T: Transport = NetlinkSocket> {
+    stream: &'a mut NetlinkStream<T>,
+    req: R,
+    span: RequestSpan,
+    done: bool,
+}
+
+impl<'a, R: NetlinkRequest, T: Transport> Dump<'a, R, T> {
+    pub(crate) fn new(stream: &'a mut NetlinkStream<T>, req: R, span: RequestSpan) -> Self {
+        Self {
+            stream,
+            req,
+            span,
+            done: false,
+        }
+    }
+
+    // A failed read abandons the rest of the response, see recv_response
+    fn drain(&mut self) {
+        let _entered = self.span.enter();
+        self.done = true;
+        while let Ok(Some(msg)) = self.stream.recv_response(&self.req, &mut self.span) {
+            self.span.received(&msg);
+        }
+    }
+}
+
+impl<R: NetlinkRequest, T: Transport> Iterator for Dump<'_, R, T> {
+    type Item = Result<R::Response>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The span isn't entered between items, while the caller runs
+        let _entered = self.span.enter();
+        while !self.done {
+            let msg = match self.stream.recv_response(&self.req, &mut self.span) {
+                Ok(Some(msg)) => msg,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            self.span.received(&msg);
+
+            match msg.error_code() {
+                Ok(Some(0)) => {}
+                Ok(Some(code)) => {
+                    let errno = Errno::from_i32(code.wrapping_neg());
+                    self.span.failed(errno);
+                    self.drain();
+                    return Some(Err(self.req.map_error(errno)));
+                }
+                Ok(None) => return Some(self.req.deserialize(&msg)),
+                Err(err) => {
+                    self.drain();
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: NetlinkRequest, T: Transport> FusedIterator for Dump<'_, R, T> {}
+
+impl<R: NetlinkRequest, T: Transport> Drop for Dump<'_, R, T> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.drain();
+        }
+    }
+}
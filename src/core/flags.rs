@@ -0,0 +1,304 @@
+use crate::constants;
+use bitflags::bitflags;
+use std::ops::BitOr;
+
+bitflags! {
+    /// Flags that have the same meaning for every Netlink message.
+    ///
+    /// The upper byte of `nlmsg_flags` holds modifiers whose meaning depends on
+    /// the kind of request, see [`GetFlags`], [`NewFlags`], [`DeleteFlags`]
+    /// and [`AckFlags`]. Combining these flags with one set of modifiers
+    /// produces a [`MessageFlags`]:
+    ///
+    /// ```rust
+    /// use netlink::{Flags, GetFlags};
+    ///
+    /// let flags = Flags::REQUEST | GetFlags::DUMP | Flags::ACK;
+    /// assert_eq!(flags.bits(), 0x305);
+    /// ```
+    ///
+    /// Modifiers of different kinds of requests share the same bits, so they
+    /// cannot be mixed:
+    ///
+    /// ```compile_fail
+    /// use netlink::{Flags, GetFlags, NewFlags};
+    ///
+    /// let flags = Flags::REQUEST | GetFlags::DUMP | NewFlags::CREATE;
+    /// ```
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct Flags: u16 {
+        /// Must be set of all request messages
+        const REQUEST = constants::REQUEST;
+        /// This message is part of a multipart message terminated by a
+        /// message with type [`crate::MessageType::Done`]
+        const MULTI = constants::MULTI;
+        /// Request an acknowledgement of success
+        const ACK = constants::ACK;
+        /// Echo this request
+        const ECHO = constants::ECHO;
+        /// Dump was inconsistent due to a sequence change
+        const DUMP_INTR = constants::DUMP_INTR;
+        /// Dump was filtered as requested
+        const DUMP_FILTERED = constants::DUMP_FILTERED;
+    }
+
+    /// Modifiers of GET requests.
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct GetFlags: u16 {
+        /// Return the complete table instead of a single entry
+        const ROOT = constants::ROOT;
+        /// Return all entries matching the criteria passed in the message
+        /// content
+        const MATCH = constants::MATCH;
+        /// Return an atomic snapshot of the table. Requires the
+        /// `CAP_NET_ADMIN` capability or an effective UID of 0.
+        const ATOMIC = constants::ATOMIC;
+        /// Return all entries. Equivalent to [`GetFlags::ROOT`] OR
+        /// [`GetFlags::MATCH`].
+        const DUMP = constants::DUMP;
+    }
+
+    /// Modifiers of NEW requests.
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct NewFlags: u16 {
+        /// Replace an existing object
+        const REPLACE = constants::REPLACE;
+        /// Don't replace if the object already exists
+        const EXCL = constants::EXCL;
+        /// Create object if it doesn't already exist
+        const CREATE = constants::CREATE;
+        /// Add to the end of the object list
+        const APPEND = constants::APPEND;
+    }
+
+    /// Modifiers of DELETE requests.
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct DeleteFlags: u16 {
+        /// Do not delete recursively
+        const NONREC = constants::NONREC;
+        /// Delete multiple objects
+        const BULK = constants::BULK;
+    }
+
+    /// Modifiers of the ACK sent in response to a request with
+    /// [`Flags::ACK`].
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct AckFlags: u16 {
+        /// The request was capped, so the error message doesn't include its
+        /// payload
+        const CAPPED = constants::CAPPED;
+        /// Extended ACK attributes are included in the error message
+        const ACK_TLVS = constants::ACK_TLVS;
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A set of flags that can be written to `nlmsg_flags`. This is implemented by
+/// [`Flags`], each set of modifiers and [`MessageFlags`].
+pub trait FlagSet: private::Sealed + Copy {
+    /// The raw value of `nlmsg_flags`.
+    fn bits(self) -> u16;
+}
+
+/// A set of modifiers for one kind of request. See [`MessageFlags`].
+pub trait Modifiers: FlagSet + Default {
+    /// Convert raw flags into modifiers, dropping the bits that are not in the
+    /// upper byte.
+    fn from_raw(bits: u16) -> Self;
+}
+
+/// [`Flags`] combined with the modifiers of one kind of request, like
+/// `Flags::REQUEST | GetFlags::DUMP`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+pub struct MessageFlags<M> {
+    pub flags: Flags,
+    pub modifiers: M,
+}
+
+impl<M: Modifiers> MessageFlags<M> {
+    #[must_use]
+    pub fn new(flags: Flags, modifiers: M) -> Self {
+        Self { flags, modifiers }
+    }
+
+    /// Interpret the raw value of `nlmsg_flags` as flags of this kind of
+    /// request. Unknown bits are dropped.
+    #[must_use]
+    pub fn from_bits_truncate(bits: u16) -> Self {
+        Self {
+            flags: Flags::from_bits_truncate(bits),
+            modifiers: M::from_raw(bits),
+        }
+    }
+
+    /// The raw value of `nlmsg_flags`.
+    #[must_use]
+    pub fn bits(&self) -> u16 {
+        self.flags.bits() | self.modifiers.bits()
+    }
+}
+
+impl Flags {
+    /// Interpret the modifiers in the raw value of `nlmsg_flags` as those of
+    /// the given kind of request.
+    #[must_use]
+    pub fn with_modifiers<M: Modifiers>(bits: u16) -> MessageFlags<M> {
+        MessageFlags::from_bits_truncate(bits)
+    }
+}
+
+impl private::Sealed for Flags {}
+
+impl FlagSet for Flags {
+    fn bits(self) -> u16 {
+        Flags::bits(&self)
+    }
+}
+
+impl<M: Modifiers> private::Sealed for MessageFlags<M> {}
+
+impl<M: Modifiers> FlagSet for MessageFlags<M> {
+    fn bits(self) -> u16 {
+        MessageFlags::bits(&self)
+    }
+}
+
+impl<M: Modifiers> BitOr<Flags> for MessageFlags<M> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Flags) -> Self::Output {
+        Self::new(self.flags | rhs, self.modifiers)
+    }
+}
+
+impl<M: Modifiers + BitOr<Output = M>> BitOr<M> for MessageFlags<M> {
+    type Output = Self;
+
+    fn bitor(self, rhs: M) -> Self::Output {
+        Self::new(self.flags, self.modifiers | rhs)
+    }
+}
+
+macro_rules! impl_modifiers {
+    ($($typ:ident),*) => {
+        $(
+            impl private::Sealed for $typ {}
+
+            impl FlagSet for $typ {
+                fn bits(self) -> u16 {
+                    $typ::bits(&self)
+                }
+            }
+
+            impl Modifiers for $typ {
+                fn from_raw(bits: u16) -> Self {
+                    $typ::from_bits_truncate(bits)
+                }
+            }
+
+            impl BitOr<$typ> for Flags {
+                type Output = MessageFlags<$typ>;
+
+                fn bitor(self, rhs: $typ) -> Self::Output {
+                    MessageFlags::new(self, rhs)
+                }
+            }
+
+            impl BitOr<Flags> for $typ {
+                type Output = MessageFlags<$typ>;
+
+                fn bitor(self, rhs: Flags) -> Self::Output {
+                    MessageFlags::new(rhs, self)
+                }
+            }
+        )*
+    };
+}
+
+impl_modifiers!(GetFlags, NewFlags, DeleteFlags, AckFlags);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_share_the_upper_byte() {
+        let get = Flags::with_modifiers::<GetFlags>(0x0301);
+        assert_eq!(get.flags, Flags::REQUEST);
+        assert_eq!(get.modifiers, GetFlags::DUMP);
+
+        let new = Flags::with_modifiers::<NewFlags>(0x0605);
+        assert_eq!(new.flags, Flags::REQUEST | Flags::ACK);
+        assert_eq!(new.modifiers, NewFlags::CREATE | NewFlags::EXCL);
+
+        let delete = Flags::with_modifiers::<DeleteFlags>(0x0205);
+        assert_eq!(delete.modifiers, DeleteFlags::BULK);
+
+        let ack = Flags::with_modifiers::<AckFlags>(0x0300);
+        assert_eq!(ack.flags, Flags::empty());
+        assert_eq!(ack.modifiers, AckFlags::CAPPED | AckFlags::ACK_TLVS);
+    }
+
+    #[test]
+    fn known_bits_round_trip() {
+        for bits in [0x0001, 0x0022, 0x0705, 0x073f] {
+            assert_eq!(
+                MessageFlags::<GetFlags>::from_bits_truncate(bits).bits(),
+                bits
+            );
+        }
+        for bits in [0x0f01, 0x0d3f] {
+            assert_eq!(
+                MessageFlags::<NewFlags>::from_bits_truncate(bits).bits(),
+                bits
+            );
+        }
+        assert_eq!(
+            MessageFlags::<DeleteFlags>::from_bits_truncate(0x0305).bits(),
+            0x0305
+        );
+        assert_eq!(
+            MessageFlags::<AckFlags>::from_bits_truncate(0x0302).bits(),
+            0x0302
+        );
+    }
+
+    #[test]
+    fn unknown_bits_are_dropped() {
+        // Bit 6 isn't a flag, and the upper nibble isn't a modifier of any
+        // kind of request
+        let get = MessageFlags::<GetFlags>::from_bits_truncate(0xf841);
+        assert_eq!(get.flags, Flags::REQUEST);
+        assert_eq!(get.modifiers, GetFlags::empty());
+        assert_eq!(get.bits(), 0x0001);
+
+        // APPEND is a NEW modifier, which GET requests don't have
+        let new = MessageFlags::<NewFlags>::from_bits_truncate(0xf841);
+        assert_eq!(new.modifiers, NewFlags::APPEND);
+        assert_eq!(new.bits(), 0x0801);
+
+        let delete = MessageFlags::<DeleteFlags>::from_bits_truncate(0xfc01);
+        assert_eq!(delete.modifiers, DeleteFlags::empty());
+        assert_eq!(delete.bits(), 0x0001);
+
+        let ack = MessageFlags::<AckFlags>::from_bits_truncate(0xfc00);
+        assert_eq!(ack.modifiers, AckFlags::empty());
+        assert_eq!(ack.bits(), 0);
+    }
+
+    #[test]
+    fn flags_combine_with_modifiers() {
+        let flags = Flags::REQUEST | GetFlags::ROOT | GetFlags::MATCH | Flags::ACK;
+        assert_eq!(
+            flags,
+            MessageFlags::new(Flags::REQUEST | Flags::ACK, GetFlags::DUMP)
+        );
+        assert_eq!(flags.bits(), 0x0305);
+
+        let flags = NewFlags::CREATE | Flags::REQUEST;
+        assert_eq!(FlagSet::bits(flags), 0x0401);
+    }
+}
@@ -1,8 +1,40 @@
 pub mod constants;
 pub use constants::*;
 
+pub mod codec;
+pub use codec::*;
+
+pub mod dump;
+pub use dump::*;
+
+pub mod flags;
+pub use flags::*;
+
+pub mod pcap;
+pub use pcap::*;
+
+pub mod pretty;
+pub use pretty::*;
+
+pub mod record;
+pub use record::*;
+
+pub mod request;
+pub use request::*;
+
 pub mod socket;
 pub use socket::*;
 
+pub mod stats;
+pub use stats::*;
+
+pub mod stream;
+pub use stream::*;
+
+mod trace;
+
+pub mod transport;
+pub use transport::*;
+
 pub mod types;
 pub use types::*;
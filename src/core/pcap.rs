@@ -0,0 +1,452 @@
+use crate::codec::split_messages;
+use crate::record::Direction;
+use crate::stats::SocketMemInfo;
+use crate::transport::Transport;
+use crate::types::NetlinkMessage;
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Link-layer header type of Netlink captures. See
+/// [`LINKTYPE_NETLINK`](https://www.tcpdump.org/linktypes/LINKTYPE_NETLINK.html).
+pub const LINKTYPE_NETLINK: u32 = 253;
+
+/// Hardware type of Netlink devices, used in the cooked capture header
+const ARPHRD_NETLINK: u16 = 824;
+
+/// Packet types used in the cooked capture header
+const PACKET_HOST: u16 = 0;
+const PACKET_OUTGOING: u16 = 4;
+
+/// Protocol of the sockets opened by [`crate::NetlinkSocket`]
+const NETLINK_ROUTE: u16 = 0;
+
+/// Magic numbers of captures with microsecond and nanosecond timestamps
+const MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+
+/// Block type of the section header that starts pcapng captures. It reads the
+/// same in both byte orders.
+const PCAPNG_MAGIC: u32 = 0x0a0d_0d0a;
+
+/// Length of the cooked header that precedes every Netlink packet
+const COOKED_HEADER_LEN: usize = 16;
+
+/// Largest packet that is stored in a capture, or read from one
+const SNAPLEN: u32 = 262_144;
+
+/// Writes Netlink messages to a file in the classic
+/// [pcap](https://wiki.wireshark.org/Development/LibpcapFileFormat) format,
+/// with the [`LINKTYPE_NETLINK`] link-layer header. This is what the `nlmon`
+/// device produces, so the capture can be opened in Wireshark with its netlink
+/// and rtnetlink dissectors.
+pub struct PcapWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Start a new capture by writing the pcap file header to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if writing to `writer` fails.
+    pub fn new(mut writer: W) -> Result<Self> {
+        let mut header = vec![];
+        header.extend_from_slice(&MAGIC_MICROS.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&4u16.to_le_bytes());
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&SNAPLEN.to_le_bytes());
+        header.extend_from_slice(&LINKTYPE_NETLINK.to_le_bytes());
+
+        writer.write_all(&header).map_err(Error::ErrCapture)?;
+        Ok(Self { writer })
+    }
+
+    /// Write a datagram to the capture, timestamped with the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if writing to the underlying writer fails.
+    pub fn write_datagram(&mut self, direction: Direction, datagram: &[u8]) -> Result<()> {
+        self.write_datagram_at(SystemTime::now(), direction, datagram)
+    }
+
+    /// Write a datagram to the capture with the given timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if writing to the underlying writer fails.
+    pub fn write_datagram_at(
+        &mut self,
+        time: SystemTime,
+        direction: Direction,
+        datagram: &[u8],
+    ) -> Result<()> {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs: u32 = since_epoch.as_secs().try_into().unwrap_or(u32::MAX);
+
+        // Linux "cooked" header, which identifies the packet as Netlink and
+        // records its direction. These fields are in network byte order.
+        let packet_type = match direction {
+            Direction::Sent => PACKET_OUTGOING,
+            Direction::Received => PACKET_HOST,
+        };
+        let mut packet = vec![];
+        packet.extend_from_slice(&packet_type.to_be_bytes());
+        packet.extend_from_slice(&ARPHRD_NETLINK.to_be_bytes());
+        packet.extend_from_slice(&0u16.to_be_bytes());
+        packet.extend_from_slice(&[0u8; 8]);
+        packet.extend_from_slice(&NETLINK_ROUTE.to_be_bytes());
+        packet.extend_from_slice(datagram);
+
+        let orig_len: u32 = packet
+            .len()
+            .try_into()
+            .map_err(|_| Error::ErrValueConversion)?;
+        let incl_len = orig_len.min(SNAPLEN);
+
+        let mut record = vec![];
+        record.extend_from_slice(&secs.to_le_bytes());
+        record.extend_from_slice(&since_epoch.subsec_micros().to_le_bytes());
+        record.extend_from_slice(&incl_len.to_le_bytes());
+        record.extend_from_slice(&orig_len.to_le_bytes());
+        record.extend_from_slice(&packet[..incl_len as usize]);
+
+        self.writer.write_all(&record).map_err(Error::ErrCapture)?;
+
+        // Flush every packet, so a capture survives the process crashing
+        self.writer.flush().map_err(Error::ErrCapture)
+    }
+
+    /// Consume the writer and return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Wraps a [`Transport`] and writes every datagram that passes through it to a
+/// pcap capture. See [`PcapWriter`].
+///
+/// This is an alternative to setting up an `nlmon` device that only captures
+/// the traffic of one [`crate::NetlinkStream`]:
+///
+/// ```rust,no_run
+/// use netlink::{NetlinkSocket, NetlinkStream, PcapTap};
+///
+/// # fn main() -> netlink::Result<()> {
+/// let sock = NetlinkSocket::connect()?;
+/// let mut conn = NetlinkStream::new(PcapTap::create(sock, "netlink.pcap")?);
+/// conn.list_routes()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PcapTap<T: Transport, W: Write = BufWriter<File>> {
+    inner: T,
+    writer: PcapWriter<W>,
+}
+
+impl<T: Transport> PcapTap<T, BufWriter<File>> {
+    /// Capture the datagrams carried by `inner` to a new file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the file cannot be created.
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> Result<Self> {
+        let file = File::create(path).map_err(Error::ErrCapture)?;
+        Self::new(inner, BufWriter::new(file))
+    }
+}
+
+impl<T: Transport, W: Write> PcapTap<T, W> {
+    /// Capture the datagrams carried by `inner` to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if writing to `writer` fails.
+    pub fn new(inner: T, writer: W) -> Result<Self> {
+        let writer = PcapWriter::new(writer)?;
+        Ok(Self { inner, writer })
+    }
+
+    /// Consume the tap and return the wrapped transport and writer.
+    pub fn into_inner(self) -> (T, W) {
+        (self.inner, self.writer.into_inner())
+    }
+}
+
+impl<T: Transport, W: Write> Transport for PcapTap<T, W> {
+    fn pid(&self) -> u32 {
+        self.inner.pid()
+    }
+
+    fn send(&mut self, buf: &[u8]) -> Result<()> {
+        self.inner.send(buf)?;
+        self.writer.write_datagram(Direction::Sent, buf)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        let datagram = self.inner.recv()?;
+        self.writer.write_datagram(Direction::Received, &datagram)?;
+        Ok(datagram)
+    }
+
+    fn strict_checking(&self) -> Result<bool> {
+        self.inner.strict_checking()
+    }
+
+    fn set_strict_checking(&mut self, enable: bool) -> Result<()> {
+        self.inner.set_strict_checking(enable)
+    }
+
+    fn meminfo(&self) -> Result<SocketMemInfo> {
+        self.inner.meminfo()
+    }
+}
+
+/// A packet read from a capture by a [`PcapReader`].
+#[derive(PartialEq, Clone, Debug)]
+pub struct CapturedPacket {
+    /// When the packet was captured
+    pub time: SystemTime,
+    /// Netlink protocol of the socket the packet was sent on. This is zero for
+    /// `NETLINK_ROUTE`.
+    pub protocol: u16,
+    /// The captured datagram, which contains one or more Netlink messages
+    pub datagram: Vec<u8>,
+}
+
+impl CapturedPacket {
+    /// Split the captured datagram into the Netlink messages it contains.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the datagram does not contain
+    /// well-formed Netlink messages. This can happen if it was truncated by
+    /// the capture's snapshot length.
+    pub fn messages(&self) -> Result<Vec<NetlinkMessage>> {
+        let messages = split_messages(&self.datagram)?;
+        Ok(messages
+            .into_iter()
+            .map(|(hdr, payload)| NetlinkMessage::new(hdr.into_descriptor(), payload))
+            .collect())
+    }
+}
+
+/// Reads Netlink packets from a capture in the classic pcap format with the
+/// [`LINKTYPE_NETLINK`] link-layer header, like those taken on an `nlmon`
+/// device with `tcpdump -i nlmon0 -w netlink.pcap`, or written by a
+/// [`PcapWriter`].
+///
+/// Captures in the newer pcapng format, which Wireshark and `dumpcap` write
+/// by default, are rejected with [`Error::ErrPcapngCapture`]. Convert them
+/// with `editcap -F pcap netlink.pcapng netlink.pcap` first.
+///
+/// ```rust,no_run
+/// use netlink::route::decode_message;
+/// use netlink::PcapReader;
+///
+/// # fn main() -> netlink::Result<()> {
+/// for packet in PcapReader::open("netlink.pcap")? {
+///     for msg in packet?.messages()? {
+///         println!("{:?}", decode_message(&msg)?);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PcapReader<R: Read> {
+    reader: R,
+    is_big_endian: bool,
+    is_nanos: bool,
+    snaplen: u32,
+}
+
+impl PcapReader<BufReader<File>> {
+    /// Read the capture stored in the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the file cannot be read, or is not a
+    /// Netlink capture.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path).map_err(Error::ErrCapture)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Read the capture from `reader`, starting with the pcap file header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if `reader` fails, or does not contain a
+    /// Netlink capture.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header).map_err(Error::ErrCapture)?;
+
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let (is_big_endian, is_nanos) = match magic {
+            MAGIC_MICROS => (false, false),
+            MAGIC_NANOS => (false, true),
+            _ if magic.swap_bytes() == MAGIC_MICROS => (true, false),
+            _ if magic.swap_bytes() == MAGIC_NANOS => (true, true),
+            PCAPNG_MAGIC => return Err(Error::ErrPcapngCapture),
+            _ => return Err(Error::ErrMalformedCapture),
+        };
+
+        let mut pcap = Self {
+            reader,
+            is_big_endian,
+            is_nanos,
+            snaplen: SNAPLEN,
+        };
+
+        // Packets are never longer than this, so larger lengths are corrupt
+        pcap.snaplen = pcap.u32_at(&header, 16).min(SNAPLEN);
+
+        let linktype = pcap.u32_at(&header, 20);
+        if linktype != LINKTYPE_NETLINK {
+            return Err(Error::ErrUnsupportedLinkType(linktype));
+        }
+
+        Ok(pcap)
+    }
+
+    /// Read the next packet in the capture. Returns [`None`] at the end of the
+    /// capture.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the underlying reader fails, or the
+    /// packet is malformed or longer than the capture's snapshot length.
+    pub fn read_packet(&mut self) -> Result<Option<CapturedPacket>> {
+        let mut record = [0u8; 16];
+        match self.reader.read_exact(&mut record) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(Error::ErrCapture(err)),
+        }
+
+        let secs = self.u32_at(&record, 0);
+        let fraction = self.u32_at(&record, 4);
+        let incl_len = self.u32_at(&record, 8);
+        if incl_len > self.snaplen {
+            return Err(Error::ErrMalformedCapture);
+        }
+
+        let mut packet = vec![0u8; incl_len as usize];
+        self.reader
+            .read_exact(&mut packet)
+            .map_err(Error::ErrCapture)?;
+
+        if packet.len() < COOKED_HEADER_LEN {
+            return Err(Error::ErrUnexpectedEof);
+        }
+
+        let subsec = if self.is_nanos {
+            Duration::from_nanos(fraction.into())
+        } else {
+            Duration::from_micros(fraction.into())
+        };
+        let time = UNIX_EPOCH + Duration::from_secs(secs.into()) + subsec;
+        let protocol = u16::from_be_bytes([packet[14], packet[15]]);
+        let datagram = packet.split_off(COOKED_HEADER_LEN);
+
+        Ok(Some(CapturedPacket {
+            time,
+            protocol,
+            datagram,
+        }))
+    }
+
+    fn u32_at(&self, bytes: &[u8], offset: usize) -> u32 {
+        let mut value = [0u8; 4];
+        value.copy_from_slice(&bytes[offset..offset + 4]);
+        if self.is_big_endian {
+            u32::from_be_bytes(value)
+        } else {
+            u32::from_le_bytes(value)
+        }
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = Result<CapturedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Global header, then the record header of the first packet
+    const RECORD_OFFSET: usize = 24;
+
+    fn capture(datagram: &[u8]) -> Result<Vec<u8>> {
+        let mut writer = PcapWriter::new(vec![])?;
+        writer.write_datagram_at(UNIX_EPOCH, Direction::Received, datagram)?;
+        Ok(writer.into_inner())
+    }
+
+    #[test]
+    fn packets_round_trip() -> Result<()> {
+        let bytes = capture(&[1, 2, 3, 4])?;
+        let mut reader = PcapReader::new(bytes.as_slice())?;
+
+        let packet = reader.read_packet()?.ok_or(Error::ErrUnexpectedEof)?;
+        assert_eq!(packet.datagram, [1, 2, 3, 4]);
+        assert_eq!(packet.protocol, NETLINK_ROUTE);
+        assert!(reader.read_packet()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_packet_is_rejected() -> Result<()> {
+        let mut bytes = capture(&[1, 2, 3, 4])?;
+        let incl_len = RECORD_OFFSET + 8;
+        bytes[incl_len..incl_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut reader = PcapReader::new(bytes.as_slice())?;
+        assert!(matches!(
+            reader.read_packet(),
+            Err(Error::ErrMalformedCapture)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn packet_longer_than_snaplen_is_rejected() -> Result<()> {
+        let mut bytes = capture(&[0; 64])?;
+        bytes[16..20].copy_from_slice(&32u32.to_le_bytes());
+
+        let mut reader = PcapReader::new(bytes.as_slice())?;
+        assert!(matches!(
+            reader.read_packet(),
+            Err(Error::ErrMalformedCapture)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn pcapng_capture_is_rejected() {
+        // The start of the section header block dumpcap writes
+        #[rustfmt::skip]
+        let header = [
+            0x0a, 0x0d, 0x0d, 0x0a, 0x9c, 0x00, 0x00, 0x00,
+            0x4d, 0x3c, 0x2b, 0x1a, 0x01, 0x00, 0x00, 0x00,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ];
+        assert!(matches!(
+            PcapReader::new(header.as_slice()),
+            Err(Error::ErrPcapngCapture)
+        ));
+    }
+}
@@ -0,0 +1,610 @@
+use crate::bytes::SliceReader;
+use crate::codec::split_messages;
+use crate::flags::{AckFlags, DeleteFlags, Flags, GetFlags, NewFlags};
+use crate::types::{MessageType, NetlinkMessage};
+use crate::Result;
+use std::fmt::{self, Display, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A decoded piece of a message, displayed as `name=value`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub value: FieldValue,
+}
+
+impl Field {
+    /// A field with a decoded value.
+    pub fn new<N: Into<String>>(name: N, value: FieldValue) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+
+    /// A field whose value is displayed as-is.
+    pub fn text<N: Into<String>, V: Display>(name: N, value: &V) -> Self {
+        Self::new(name, FieldValue::Text(value.to_string()))
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// Value of a decoded [`Field`].
+#[derive(PartialEq, Clone, Debug)]
+pub enum FieldValue {
+    /// A scalar value, like a number or an address
+    Text(String),
+    /// A fixed-layout structure, displayed as `{a=1, b=2}`
+    Struct(Vec<Field>),
+    /// A list of attributes, displayed as `[A=1, B=2]`
+    Attributes(Vec<Field>),
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(text) => f.write_str(text),
+            FieldValue::Struct(fields) => write_list(f, "{", fields, "}"),
+            FieldValue::Attributes(fields) => write_list(f, "[", fields, "]"),
+        }
+    }
+}
+
+fn write_list(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    fields: &[Field],
+    close: &str,
+) -> fmt::Result {
+    f.write_str(open)?;
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{field}")?;
+    }
+    f.write_str(close)
+}
+
+/// How the value of an attribute is interpreted. See [`AttrSpec`].
+#[derive(Copy, Clone, Debug)]
+pub enum AttrKind {
+    U8,
+    U16,
+    U32,
+    U64,
+    I32,
+    /// A NUL-terminated string
+    Str,
+    /// An IPv4 or IPv6 address, depending on the length
+    Addr,
+    /// A hardware address, like a MAC address
+    HwAddr,
+    /// Opaque bytes, displayed as hex
+    Bytes,
+    /// Nested attributes, described by another table
+    Nested(&'static [AttrSpec]),
+}
+
+/// Describes one attribute type of a protocol, so it can be decoded by
+/// [`decode_attributes`].
+#[derive(Copy, Clone, Debug)]
+pub struct AttrSpec {
+    pub typ: u16,
+    pub name: &'static str,
+    pub kind: AttrKind,
+}
+
+impl AttrSpec {
+    #[must_use]
+    pub const fn new(typ: u16, name: &'static str, kind: AttrKind) -> Self {
+        Self { typ, name, kind }
+    }
+}
+
+/// Decode a list of attributes with the names and kinds described by `specs`.
+/// Attributes that aren't described are displayed by number, with their value
+/// as hex.
+///
+/// # Errors
+///
+/// Returns an [`crate::Error`] if the attributes are malformed.
+pub fn decode_attributes(payload: &[u8], specs: &[AttrSpec]) -> Result<Vec<Field>> {
+    let mut reader = SliceReader::new(payload);
+    let mut fields = vec![];
+
+    while !reader.is_empty() {
+        let (typ, value) = reader.read_attribute()?;
+        let field = match specs.iter().find(|spec| spec.typ == typ) {
+            Some(spec) => Field::new(spec.name, decode_value(spec.kind, value)),
+            None => Field::text(typ.to_string(), &hex(value)),
+        };
+        fields.push(field);
+    }
+
+    Ok(fields)
+}
+
+// Values that don't have the expected size are shown as hex, rather than
+// failing the whole message.
+fn decode_value(kind: AttrKind, value: &[u8]) -> FieldValue {
+    let text = match kind {
+        AttrKind::U8 => <[u8; 1]>::try_from(value).map(|v| v[0].to_string()).ok(),
+        AttrKind::U16 => value
+            .try_into()
+            .map(|v| u16::from_le_bytes(v).to_string())
+            .ok(),
+        AttrKind::U32 => value
+            .try_into()
+            .map(|v| u32::from_le_bytes(v).to_string())
+            .ok(),
+        AttrKind::U64 => value
+            .try_into()
+            .map(|v| u64::from_le_bytes(v).to_string())
+            .ok(),
+        AttrKind::I32 => value
+            .try_into()
+            .map(|v| i32::from_le_bytes(v).to_string())
+            .ok(),
+        AttrKind::Str => Some(format!("{:?}", crate::bytes::deserialize_ascii(value))),
+        AttrKind::Addr => decode_addr(value).map(|addr| addr.to_string()),
+        AttrKind::HwAddr => Some(hw_addr(value)),
+        AttrKind::Bytes => None,
+        AttrKind::Nested(specs) => {
+            if let Ok(fields) = decode_attributes(value, specs) {
+                return FieldValue::Attributes(fields);
+            }
+            None
+        }
+    };
+
+    FieldValue::Text(text.unwrap_or_else(|| hex(value)))
+}
+
+fn decode_addr(value: &[u8]) -> Option<IpAddr> {
+    if let Ok(octets) = <[u8; 4]>::try_from(value) {
+        return Some(IpAddr::V4(Ipv4Addr::from(octets)));
+    }
+    if let Ok(octets) = <[u8; 16]>::try_from(value) {
+        return Some(IpAddr::V6(Ipv6Addr::from(octets)));
+    }
+    None
+}
+
+fn hw_addr(value: &[u8]) -> String {
+    let octets: Vec<String> = value.iter().map(|byte| format!("{byte:02x}")).collect();
+    octets.join(":")
+}
+
+/// Format bytes as a hex string, like `"\x0a\x00"`.
+pub(crate) fn hex(value: &[u8]) -> String {
+    let mut text = String::from("\"");
+    for byte in value {
+        let _ = write!(text, "\\x{byte:02x}");
+    }
+    text.push('"');
+    text
+}
+
+/// Name of an address family, like `AF_INET`.
+#[must_use]
+pub fn family_name(family: u8) -> String {
+    let name = match family {
+        0 => "AF_UNSPEC",
+        1 => "AF_UNIX",
+        2 => "AF_INET",
+        7 => "AF_BRIDGE",
+        10 => "AF_INET6",
+        16 => "AF_NETLINK",
+        17 => "AF_PACKET",
+        28 => "AF_MPLS",
+        128 => "RTNL_FAMILY_IPMR",
+        129 => "RTNL_FAMILY_IP6MR",
+        _ => return family.to_string(),
+    };
+    name.to_string()
+}
+
+/// Which modifiers the upper byte of `nlmsg_flags` holds. Their meaning
+/// depends on the kind of request.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FlagKind {
+    /// Modifiers of GET requests, like `NLM_F_DUMP`
+    Get,
+    /// Modifiers of NEW requests, like `NLM_F_CREATE`
+    New,
+    /// Modifiers of DELETE requests, like `NLM_F_NONREC`
+    Delete,
+    /// Modifiers of ACK messages, like `NLM_F_CAPPED`
+    Ack,
+    /// The upper byte has no known meaning
+    None,
+}
+
+/// Decodes the messages of one part of a Netlink protocol into a
+/// human-readable form. Implementations are registered with a
+/// [`DecoderRegistry`].
+pub trait MessageDecoder {
+    /// Name of the message type, like `RTM_NEWROUTE`. Returns [`None`] if this
+    /// decoder doesn't handle messages with this type.
+    fn type_name(&self, typ: u16) -> Option<&'static str>;
+
+    /// Which modifiers are set in the flags of messages with this type.
+    fn flag_kind(&self, typ: u16) -> FlagKind;
+
+    /// Decode the payload of a message with this type. This usually returns
+    /// a [`FieldValue::Struct`] for the fixed header, followed by
+    /// [`FieldValue::Attributes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the payload is malformed.
+    fn decode(&self, typ: u16, payload: &[u8]) -> Result<Vec<FieldValue>>;
+}
+
+/// Set of [`MessageDecoder`]s for one Netlink protocol, which turn messages
+/// into something that can be displayed, like `strace` does.
+///
+/// The default registry knows about the `NETLINK_ROUTE` link, address, route
+/// and rule messages:
+///
+/// ```rust
+/// use netlink::route::route::{RouteMessage, RouteMessageType};
+/// use netlink::route::AF_INET;
+/// use netlink::{DecoderRegistry, Flags, GetFlags, NetlinkMessage};
+///
+/// # fn main() -> netlink::Result<()> {
+/// let rthdr = RouteMessage::builder().family(AF_INET).build()?;
+/// let msg = NetlinkMessage::builder()
+///     .typ(RouteMessageType::GetRoute)
+///     .flags(Flags::REQUEST | GetFlags::DUMP)
+///     .append(rthdr)?
+///     .build();
+///
+/// let decoded = DecoderRegistry::default().decode(&msg);
+/// assert!(decoded.to_string().starts_with(
+///     "{type=RTM_GETROUTE, flags=NLM_F_REQUEST|NLM_F_DUMP, seq=0, pid=0}, {rtm_family=AF_INET, "
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecoderRegistry {
+    decoders: Vec<Box<dyn MessageDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// Create a registry without any decoders. Only the core message types,
+    /// like [`MessageType::Error`], will be decoded.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { decoders: vec![] }
+    }
+
+    /// Add a decoder. Decoders are asked whether they handle a message type
+    /// in the order they were registered.
+    pub fn register<D: MessageDecoder + 'static>(&mut self, decoder: D) {
+        self.decoders.push(Box::new(decoder));
+    }
+
+    /// Decode a message. Parts of the message that cannot be decoded are
+    /// displayed as hex.
+    #[must_use]
+    pub fn decode(&self, msg: &NetlinkMessage) -> DecodedMessage {
+        let typ = msg.header.typ;
+        let decoder = self.decoder(typ);
+        let header = self.decode_header(msg);
+
+        let payload = match decoder {
+            _ if typ == u16::from(MessageType::Error) => self.decode_error(&msg.payload),
+            Some(decoder) if core_type_name(typ).is_none() => decoder.decode(typ, &msg.payload),
+            _ => Ok(vec![]),
+        };
+
+        let payload = match payload {
+            Ok(parts) if parts.is_empty() && !msg.payload.is_empty() => {
+                vec![FieldValue::Text(hex(&msg.payload))]
+            }
+            Ok(parts) => parts,
+            Err(_) => vec![FieldValue::Text(hex(&msg.payload))],
+        };
+
+        DecodedMessage { header, payload }
+    }
+
+    fn decoder(&self, typ: u16) -> Option<&dyn MessageDecoder> {
+        self.decoders
+            .iter()
+            .find(|d| d.type_name(typ).is_some())
+            .map(AsRef::as_ref)
+    }
+
+    fn decode_header(&self, msg: &NetlinkMessage) -> Vec<Field> {
+        let typ = msg.header.typ;
+
+        let (type_name, flag_kind) = match (core_type_name(typ), self.decoder(typ)) {
+            (Some(name), _) => {
+                let kind = if typ == u16::from(MessageType::Error) {
+                    FlagKind::Ack
+                } else {
+                    FlagKind::None
+                };
+                (name.to_string(), kind)
+            }
+            (None, Some(decoder)) => {
+                let name = decoder.type_name(typ).unwrap_or_default();
+                (name.to_string(), decoder.flag_kind(typ))
+            }
+            (None, None) => (typ.to_string(), FlagKind::None),
+        };
+
+        vec![
+            Field::text("type", &type_name),
+            Field::text("flags", &flag_names(msg.header.flags, flag_kind)),
+            Field::text("seq", &msg.header.seq),
+            Field::text("pid", &msg.header.pid),
+        ]
+    }
+
+    // Error messages hold the negative errno and the header of the request,
+    // which may be followed by its payload.
+    fn decode_error(&self, payload: &[u8]) -> Result<Vec<FieldValue>> {
+        let mut reader = SliceReader::new(payload);
+        // A malformed payload may hold i32::MIN, which has no negation
+        let errno = reader.read::<i32>()?.wrapping_neg();
+
+        let error = if errno == 0 {
+            "0".to_string()
+        } else {
+            let name = nix::errno::Errno::from_i32(errno);
+            format!("-{name:?}")
+        };
+
+        let mut fields = vec![Field::text("error", &error)];
+        if let Ok(request) = split_messages(reader.take(reader.remaining())?) {
+            if let Some((hdr, payload)) = request.into_iter().next() {
+                let msg = self.decode(&NetlinkMessage::new(hdr.into_descriptor(), payload));
+                fields.push(Field::new("msg", FieldValue::Struct(msg.header)));
+            }
+        }
+
+        Ok(vec![FieldValue::Struct(fields)])
+    }
+}
+
+impl Default for DecoderRegistry {
+    /// A registry with the decoders for `NETLINK_ROUTE`.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(crate::route::link::LinkDecoder);
+        registry.register(crate::route::addr::AddrDecoder);
+        registry.register(crate::route::route::RouteDecoder);
+        registry.register(crate::route::rule::RuleDecoder);
+        registry
+    }
+}
+
+/// A message decoded by a [`DecoderRegistry`]. This is displayed like
+/// `{type=RTM_NEWROUTE, flags=NLM_F_MULTI, seq=1, pid=42}, {rtm_family=AF_INET, ...}, [RTA_TABLE=254, ...]`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DecodedMessage {
+    /// Fields of the Netlink header
+    pub header: Vec<Field>,
+    /// The decoded parts of the payload
+    pub payload: Vec<FieldValue>,
+}
+
+impl Display for DecodedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_list(f, "{", &self.header, "}")?;
+        for part in &self.payload {
+            write!(f, ", {part}")?;
+        }
+        Ok(())
+    }
+}
+
+impl NetlinkMessage {
+    /// Decode the message as a `NETLINK_ROUTE` message, like `strace` does,
+    /// with the type, flags and attributes shown by name. This is the same as
+    /// decoding it with [`DecoderRegistry::default`]. Messages of other
+    /// protocols can be decoded by a registry with their own decoders.
+    ///
+    /// ```rust
+    /// use netlink::route::route::{RouteMessage, RouteMessageType};
+    /// use netlink::route::AF_INET;
+    /// use netlink::{Flags, GetFlags, NetlinkMessage};
+    ///
+    /// # fn main() -> netlink::Result<()> {
+    /// let rthdr = RouteMessage::builder().family(AF_INET).build()?;
+    /// let msg = NetlinkMessage::builder()
+    ///     .typ(RouteMessageType::GetRoute)
+    ///     .flags(Flags::REQUEST | GetFlags::DUMP)
+    ///     .append(rthdr)?
+    ///     .build();
+    ///
+    /// println!("{}", msg.pretty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn pretty(&self) -> DecodedMessage {
+        DecoderRegistry::default().decode(self)
+    }
+}
+
+// The protocol of the socket isn't known here, so only the header is decoded.
+// The payload can be decoded with `NetlinkMessage::pretty`, or a
+// `DecoderRegistry` for the protocol.
+impl Display for NetlinkMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = DecoderRegistry::new().decode_header(self);
+        write_list(f, "{", &header, "}")?;
+        write!(f, ", {} bytes", self.payload.len())
+    }
+}
+
+/// Name of a `NETLINK_ROUTE` message type, like `RTM_NEWROUTE`.
+#[cfg(feature = "tracing")]
+pub(crate) fn message_type_name(typ: u16) -> Option<&'static str> {
+    core_type_name(typ)
+        .or_else(|| crate::route::link::LinkDecoder.type_name(typ))
+        .or_else(|| crate::route::addr::AddrDecoder.type_name(typ))
+        .or_else(|| crate::route::route::RouteDecoder.type_name(typ))
+        .or_else(|| crate::route::rule::RuleDecoder.type_name(typ))
+}
+
+fn core_type_name(typ: u16) -> Option<&'static str> {
+    match typ {
+        1 => Some("NLMSG_NOOP"),
+        2 => Some("NLMSG_ERROR"),
+        3 => Some("NLMSG_DONE"),
+        4 => Some("NLMSG_OVERRUN"),
+        _ => None,
+    }
+}
+
+fn flag_names(flags: u16, kind: FlagKind) -> String {
+    let mut names = vec![];
+    let mut remaining = flags;
+
+    let mut take = |bits: u16, name: &str| {
+        if bits != 0 && remaining & bits == bits {
+            names.push(name.to_string());
+            remaining &= !bits;
+        }
+    };
+
+    take(Flags::REQUEST.bits(), "NLM_F_REQUEST");
+    take(Flags::MULTI.bits(), "NLM_F_MULTI");
+    take(Flags::ACK.bits(), "NLM_F_ACK");
+    take(Flags::ECHO.bits(), "NLM_F_ECHO");
+    take(Flags::DUMP_INTR.bits(), "NLM_F_DUMP_INTR");
+    take(Flags::DUMP_FILTERED.bits(), "NLM_F_DUMP_FILTERED");
+
+    match kind {
+        FlagKind::Get => {
+            take(GetFlags::DUMP.bits(), "NLM_F_DUMP");
+            take(GetFlags::ROOT.bits(), "NLM_F_ROOT");
+            take(GetFlags::MATCH.bits(), "NLM_F_MATCH");
+            take(GetFlags::ATOMIC.bits(), "NLM_F_ATOMIC");
+        }
+        FlagKind::New => {
+            take(NewFlags::REPLACE.bits(), "NLM_F_REPLACE");
+            take(NewFlags::EXCL.bits(), "NLM_F_EXCL");
+            take(NewFlags::CREATE.bits(), "NLM_F_CREATE");
+            take(NewFlags::APPEND.bits(), "NLM_F_APPEND");
+        }
+        FlagKind::Delete => {
+            take(DeleteFlags::NONREC.bits(), "NLM_F_NONREC");
+            take(DeleteFlags::BULK.bits(), "NLM_F_BULK");
+        }
+        FlagKind::Ack => {
+            take(AckFlags::CAPPED.bits(), "NLM_F_CAPPED");
+            take(AckFlags::ACK_TLVS.bits(), "NLM_F_ACK_TLVS");
+        }
+        FlagKind::None => {}
+    }
+
+    if remaining != 0 {
+        names.push(format!("{remaining:#x}"));
+    }
+    if names.is_empty() {
+        return "0".to_string();
+    }
+    names.join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::route::RouteMessageType;
+
+    #[test]
+    fn malformed_error_is_decoded() {
+        let msg = NetlinkMessage::builder().typ(MessageType::Error).build();
+        let msg = NetlinkMessage::new(msg.header, i32::MIN.to_le_bytes().to_vec());
+
+        let decoded = DecoderRegistry::new().decode(&msg);
+        assert!(decoded.to_string().starts_with("{type=NLMSG_ERROR"));
+    }
+
+    fn message(typ: RouteMessageType, payload: &[u8]) -> NetlinkMessage {
+        let msg = NetlinkMessage::builder()
+            .typ(typ)
+            .flags(Flags::MULTI)
+            .build();
+        NetlinkMessage::new(msg.header, payload.to_vec())
+    }
+
+    // The kernel's loopback interface, cut after a few attributes
+    #[rustfmt::skip]
+    const KERNEL_LINK: &[u8] = &[
+        0x00, 0x00, 0x04, 0x03, 0x01, 0x00, 0x00, 0x00,
+        0x49, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x07, 0x00, 0x03, 0x00, 0x6c, 0x6f, 0x00, 0x00,
+        0x08, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00,
+        0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    // 10.10.0.2/24 on the interface with index 6
+    #[rustfmt::skip]
+    const KERNEL_ADDR: &[u8] = &[
+        0x02, 0x18, 0x80, 0x00, 0x06, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x01, 0x00, 0x0a, 0x0a, 0x00, 0x02,
+        0x08, 0x00, 0x02, 0x00, 0x0a, 0x0a, 0x00, 0x02,
+        0x0c, 0x00, 0x03, 0x00, 0x6e, 0x6c, 0x74, 0x65,
+        0x73, 0x74, 0x30, 0x00, 0x08, 0x00, 0x08, 0x00,
+        0x80, 0x00, 0x00, 0x00,
+    ];
+
+    // 192.0.2.0/24 dev eth0 proto kernel scope link src 192.0.2.2
+    #[rustfmt::skip]
+    const KERNEL_ROUTE: &[u8] = &[
+        0x02, 0x18, 0x00, 0x00, 0xfe, 0x02, 0xfd, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0xfe, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00,
+        0xc0, 0x00, 0x02, 0x00, 0x08, 0x00, 0x07, 0x00,
+        0xc0, 0x00, 0x02, 0x02, 0x08, 0x00, 0x04, 0x00,
+        0x04, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn kernel_link_is_decoded() {
+        let msg = message(RouteMessageType::NewLink, KERNEL_LINK);
+        let expected = concat!(
+            "{type=RTM_NEWLINK, flags=NLM_F_MULTI, seq=0, pid=0}, ",
+            "{ifi_family=AF_UNSPEC, ifi_type=772, ifi_index=1, ifi_flags=0x10049, ifi_change=0x0}, ",
+            r#"[IFLA_IFNAME="lo", IFLA_MTU=65536, IFLA_ADDRESS=00:00:00:00:00:00]"#,
+        );
+        assert_eq!(msg.pretty().to_string(), expected);
+    }
+
+    #[test]
+    fn kernel_address_is_decoded() {
+        let msg = message(RouteMessageType::NewAddr, KERNEL_ADDR);
+        let expected = concat!(
+            "{type=RTM_NEWADDR, flags=NLM_F_MULTI, seq=0, pid=0}, ",
+            "{ifa_family=AF_INET, ifa_prefixlen=24, ifa_flags=0x80, ifa_scope=0, ifa_index=6}, ",
+            r#"[IFA_ADDRESS=10.10.0.2, IFA_LOCAL=10.10.0.2, IFA_LABEL="nltest0", IFA_FLAGS=128]"#,
+        );
+        assert_eq!(msg.pretty().to_string(), expected);
+    }
+
+    #[test]
+    fn kernel_route_is_decoded() {
+        let msg = message(RouteMessageType::NewRoute, KERNEL_ROUTE);
+        let expected = concat!(
+            "{type=RTM_NEWROUTE, flags=NLM_F_MULTI, seq=0, pid=0}, ",
+            "{rtm_family=AF_INET, rtm_dst_len=24, rtm_src_len=0, rtm_tos=0, rtm_table=254, ",
+            "rtm_protocol=2, rtm_scope=253, rtm_type=1, rtm_flags=0x0}, ",
+            "[RTA_TABLE=254, RTA_DST=192.0.2.0, RTA_PREFSRC=192.0.2.2, RTA_OIF=4]",
+        );
+        assert_eq!(msg.pretty().to_string(), expected);
+    }
+}
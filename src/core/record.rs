@@ -0,0 +1,308 @@
+use crate::stats::SocketMemInfo;
+use crate::transport::Transport;
+use crate::{Error, Result};
+use nix::errno::Errno;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Whether a datagram was sent to or received from the kernel.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn marker(self) -> char {
+        match self {
+            Direction::Sent => '>',
+            Direction::Received => '<',
+        }
+    }
+}
+
+/// Wraps a [`Transport`] and writes every datagram that passes through it to
+/// a recording, which can be played back later with a [`Replayer`].
+///
+/// Recordings are plain text so they can be attached to bug reports. The first
+/// line holds the port ID, and every other line holds the direction, the
+/// sequence number of the first message and the datagram itself as hex:
+///
+/// ```text
+/// pid 4242
+/// > 0 1c0000001a0001030000000092100000020000000000000000000000
+/// < 0 1400000003000200000000009210000000000000
+/// ```
+///
+/// For example:
+///
+/// ```rust,no_run
+/// use netlink::{NetlinkSocket, NetlinkStream, Recorder};
+///
+/// # fn main() -> netlink::Result<()> {
+/// let sock = NetlinkSocket::connect()?;
+/// let mut conn = NetlinkStream::new(Recorder::create(sock, "routes.nlrec")?);
+/// conn.list_routes()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Recorder<T: Transport, W: Write = BufWriter<File>> {
+    inner: T,
+    writer: W,
+}
+
+impl<T: Transport> Recorder<T, BufWriter<File>> {
+    /// Record the datagrams carried by `inner` to a new file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the file cannot be created.
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> Result<Self> {
+        let file = File::create(path).map_err(Error::ErrRecording)?;
+        Self::new(inner, BufWriter::new(file))
+    }
+}
+
+impl<T: Transport, W: Write> Recorder<T, W> {
+    /// Record the datagrams carried by `inner` to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if writing to `writer` fails.
+    pub fn new(inner: T, mut writer: W) -> Result<Self> {
+        writeln!(writer, "pid {}", inner.pid()).map_err(Error::ErrRecording)?;
+        Ok(Self { inner, writer })
+    }
+
+    /// Consume the recorder and return the wrapped transport and writer.
+    pub fn into_inner(self) -> (T, W) {
+        (self.inner, self.writer)
+    }
+
+    fn record(&mut self, direction: Direction, datagram: &[u8]) -> Result<()> {
+        let seq = datagram
+            .get(8..12)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map_or(0, u32::from_le_bytes);
+
+        write!(self.writer, "{} {seq} ", direction.marker()).map_err(Error::ErrRecording)?;
+        for byte in datagram {
+            write!(self.writer, "{byte:02x}").map_err(Error::ErrRecording)?;
+        }
+        writeln!(self.writer).map_err(Error::ErrRecording)?;
+
+        // Flush every line, so a recording survives the process crashing
+        self.writer.flush().map_err(Error::ErrRecording)
+    }
+}
+
+impl<T: Transport, W: Write> Transport for Recorder<T, W> {
+    fn pid(&self) -> u32 {
+        self.inner.pid()
+    }
+
+    fn send(&mut self, buf: &[u8]) -> Result<()> {
+        self.inner.send(buf)?;
+        self.record(Direction::Sent, buf)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        let datagram = self.inner.recv()?;
+        self.record(Direction::Received, &datagram)?;
+        Ok(datagram)
+    }
+
+    fn strict_checking(&self) -> Result<bool> {
+        self.inner.strict_checking()
+    }
+
+    fn set_strict_checking(&mut self, enable: bool) -> Result<()> {
+        self.inner.set_strict_checking(enable)
+    }
+
+    fn meminfo(&self) -> Result<SocketMemInfo> {
+        self.inner.meminfo()
+    }
+}
+
+/// A [`Transport`] that deterministically plays back a recording made by a
+/// [`Recorder`], without talking to the kernel.
+///
+/// Every datagram that is sent must match the next one in the recording. The
+/// datagrams that were received after it are then returned by
+/// [`Transport::recv`], in order.
+///
+/// ```rust,no_run
+/// use netlink::{NetlinkStream, Replayer};
+///
+/// # fn main() -> netlink::Result<()> {
+/// let mut conn = NetlinkStream::new(Replayer::open("routes.nlrec")?);
+/// let routes = conn.list_routes()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Replayer {
+    pid: u32,
+    datagrams: VecDeque<(Direction, Vec<u8>)>,
+}
+
+impl Replayer {
+    /// Play back the recording stored in the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the file cannot be read, or is not a
+    /// valid recording.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path).map_err(Error::ErrRecording)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Play back the recording read from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if `reader` fails, or does not contain a
+    /// valid recording.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut lines = reader.lines().enumerate();
+
+        let pid = match lines.next() {
+            Some((_, line)) => {
+                let line = line.map_err(Error::ErrRecording)?;
+                line.strip_prefix("pid ")
+                    .and_then(|pid| pid.parse().ok())
+                    .ok_or(Error::ErrMalformedRecording(1))?
+            }
+            None => return Err(Error::ErrMalformedRecording(1)),
+        };
+
+        let mut datagrams = VecDeque::new();
+        for (index, line) in lines {
+            let line = line.map_err(Error::ErrRecording)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = parse_entry(&line).ok_or(Error::ErrMalformedRecording(index + 1))?;
+            datagrams.push_back(entry);
+        }
+
+        Ok(Self { pid, datagrams })
+    }
+}
+
+impl Transport for Replayer {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    fn send(&mut self, buf: &[u8]) -> Result<()> {
+        match self.datagrams.pop_front() {
+            Some((Direction::Sent, datagram)) if datagram == buf => Ok(()),
+            _ => Err(Error::ErrReplayMismatch),
+        }
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        match self.datagrams.front() {
+            Some((Direction::Received, _)) => {
+                let (_, datagram) = self.datagrams.pop_front().ok_or(Error::ErrUnexpectedEof)?;
+                Ok(datagram)
+            }
+            // Behave like a non-blocking socket with nothing left to read
+            _ => Err(Error::ErrRecvSocket(Errno::EAGAIN)),
+        }
+    }
+}
+
+fn parse_entry(line: &str) -> Option<(Direction, Vec<u8>)> {
+    let mut parts = line.split_whitespace();
+
+    let direction = match parts.next()? {
+        ">" => Direction::Sent,
+        "<" => Direction::Received,
+        _ => return None,
+    };
+
+    // The sequence number is only there for humans reading the recording
+    parts.next()?.parse::<u32>().ok()?;
+
+    let hex = parts.next()?.as_bytes();
+    let chunks = hex.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+
+    // Decode from bytes, since slicing a line with non-ASCII characters by
+    // index would panic
+    let datagram = chunks
+        .map(|pair| Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect::<Option<Vec<u8>>>()?;
+
+    Some((direction, datagram))
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    char::from(digit)
+        .to_digit(16)
+        .and_then(|value| u8::try_from(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::link::Link;
+    use crate::testing::FakeKernel;
+    use crate::NetlinkStream;
+
+    #[test]
+    fn recording_is_replayed() -> Result<()> {
+        let lo = Link {
+            typ: 772,
+            index: 1,
+            name: Some("lo".to_string()),
+            ..Default::default()
+        };
+        let kernel = FakeKernel::new().with_link(lo.clone());
+        let mut conn = NetlinkStream::new(Recorder::new(kernel, Vec::new())?);
+        assert_eq!(conn.list_links()?, std::slice::from_ref(&lo));
+
+        let (kernel, recording) = conn.into_inner().into_inner();
+        let sent: Vec<_> = recording
+            .split(|&byte| byte == b'\n')
+            .filter(|line| line.starts_with(b">"))
+            .collect();
+        assert_eq!(sent.len(), kernel.requests().len());
+
+        let mut conn = NetlinkStream::new(Replayer::from_reader(recording.as_slice())?);
+        assert_eq!(conn.get_ref().pid(), kernel.pid());
+        assert_eq!(conn.list_links()?, [lo]);
+
+        // The replayer only answers the requests in the recording
+        assert!(matches!(conn.list_routes(), Err(Error::ErrReplayMismatch)));
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_recording_is_rejected() {
+        for line in [
+            "> 0 1c000",
+            "> 0 1c0g",
+            "> 0 aéa1",
+            "> x 1c00",
+            "= 0 1c00",
+            "> 0",
+        ] {
+            let recording = format!("pid 1\n< 0 00000000\n{line}\n");
+            let err = Replayer::from_reader(recording.as_bytes()).unwrap_err();
+            assert!(matches!(err, Error::ErrMalformedRecording(3)), "{line}");
+        }
+
+        let err = Replayer::from_reader("pid x\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::ErrMalformedRecording(1)));
+    }
+}
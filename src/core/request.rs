@@ -0,0 +1,107 @@
+use crate::bytes::SliceReader;
+use crate::flags::FlagSet;
+use crate::types::{MessageType, NetlinkMessage};
+use crate::{Error, Result};
+use nix::errno::Errno;
+
+/// An operation of a Netlink protocol, like dumping the route table. This
+/// describes how the request is encoded, and how each message of the response
+/// is decoded. It is sent with [`crate::NetlinkStream::execute`], which takes
+/// care of dump iteration, ACKs and errors.
+///
+/// For example, a request for the addresses of every interface:
+///
+/// ```rust
+/// use netlink::route::addr::InterfaceAddrMessage;
+/// use netlink::route::route::RouteMessageType;
+/// use netlink::route::AF_INET;
+/// use netlink::{Flags, GetFlags, MessageFlags, NetlinkMessage, NetlinkRequest};
+///
+/// struct ListAddrs;
+///
+/// impl NetlinkRequest for ListAddrs {
+///     type Flags = MessageFlags<GetFlags>;
+///     type Response = usize;
+///
+///     fn message_type(&self) -> u16 {
+///         RouteMessageType::GetAddr.into()
+///     }
+///
+///     fn flags(&self) -> Self::Flags {
+///         Flags::REQUEST | GetFlags::DUMP
+///     }
+///
+///     fn serialize(&self) -> netlink::Result<Vec<u8>> {
+///         let ifaddrmsg = InterfaceAddrMessage::builder().family(AF_INET).build()?;
+///         let msg = NetlinkMessage::builder().append(ifaddrmsg)?.build();
+///         Ok(msg.payload)
+///     }
+///
+///     fn deserialize(&self, msg: &NetlinkMessage) -> netlink::Result<usize> {
+///         Ok(msg.payload.len())
+///     }
+/// }
+/// ```
+pub trait NetlinkRequest {
+    /// The flags the request is sent with, like `Flags::REQUEST |
+    /// GetFlags::DUMP`.
+    type Flags: FlagSet;
+
+    /// Decoded from every message the kernel responds with.
+    type Response;
+
+    /// See `nlmsg_type` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    fn message_type(&self) -> u16;
+
+    /// See `nlmsg_flags` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    fn flags(&self) -> Self::Flags;
+
+    /// Serialize the payload of the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the request cannot be serialized.
+    fn serialize(&self) -> Result<Vec<u8>>;
+
+    /// Decode one message of the response. This is never called for the
+    /// `NLMSG_ERROR` and `NLMSG_DONE` messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the message is malformed.
+    fn deserialize(&self, msg: &NetlinkMessage) -> Result<Self::Response>;
+
+    /// Turn an error reported by the kernel into an [`Error`]. Requests can
+    /// override this to give meaning to the errno.
+    fn map_error(&self, errno: Errno) -> Error {
+        Error::ErrKernel(errno)
+    }
+
+    /// Build the message that is sent to the kernel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the request cannot be serialized.
+    fn to_message(&self) -> Result<NetlinkMessage> {
+        let msg = NetlinkMessage::builder()
+            .typ(self.message_type())
+            .flags(self.flags())
+            .build();
+
+        Ok(NetlinkMessage::new(msg.header, self.serialize()?))
+    }
+}
+
+impl NetlinkMessage {
+    /// The status carried by an `NLMSG_ERROR` message, which is a negative
+    /// errno, or 0 for an ACK. Returns [`None`] for other messages.
+    pub(crate) fn error_code(&self) -> Result<Option<i32>> {
+        if self.header.typ != u16::from(MessageType::Error) {
+            return Ok(None);
+        }
+
+        SliceReader::new(&self.payload).read::<i32>().map(Some)
+    }
+}
@@ -1,22 +1,21 @@
-use crate::bytes::{aligned_size_of, serialize_aligned};
-use crate::types::{Flag, MessageType, NetlinkHeader, NetlinkMessage};
+use crate::stats::SocketMemInfo;
+use crate::transport::Transport;
 use crate::{Error, Result};
-use bincode::deserialize;
+use nix::errno::Errno;
+use nix::libc;
 use nix::sys::socket::{
     bind, recv, send, socket, AddressFamily, MsgFlags, NetlinkAddr, SockFlag, SockProtocol,
     SockType,
 };
-use nix::unistd::getpid;
-use std::io::{BufReader, BufWriter, Read, Write};
+use nix::unistd::{close, getpid};
 use std::os::fd::RawFd;
 
-/// Wraps a socket [`RawFd`] descriptor to provide safe read and write methods
-/// for sending and receiving Netlink messages. It will buffer reads and writes
-/// to the socket.
+/// Wraps a socket [`RawFd`] descriptor to provide safe methods for sending
+/// and receiving Netlink datagrams.
 ///
-/// This also keeps track of the PID and sequence numbers required to create a
-/// properly-formatted Netlink messages. See [`NetlinkMessage::build`].
-#[derive(PartialEq, Clone, Debug)]
+/// This also keeps track of the PID required to create properly-formatted
+/// Netlink messages. See [`crate::NetlinkCodec`].
+#[derive(PartialEq, Debug)]
 pub struct NetlinkSocket {
     fd: RawFd,
     pid: u32,
@@ -24,7 +23,11 @@ pub struct NetlinkSocket {
 
 impl NetlinkSocket {
     /// Initialize a new Netlink socket and connect to it.
-    fn connect() -> Result<Self> {
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] when the socket cannot be created or bound.
+    pub fn connect() -> Result<Self> {
         let fd = socket(
             AddressFamily::Netlink,
             SockType::Raw,
@@ -49,167 +52,97 @@ impl NetlinkSocket {
     }
 }
 
-impl std::io::Read for NetlinkSocket {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        recv(self.fd, buf, MsgFlags::empty())
-            .map_err(|err| std::io::Error::from_raw_os_error(err as i32))
+impl Transport for NetlinkSocket {
+    fn pid(&self) -> u32 {
+        self.pid
     }
-}
 
-impl std::io::Write for NetlinkSocket {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        send(self.fd, buf, MsgFlags::empty())
-            .map_err(|errno| std::io::Error::from_raw_os_error(errno as i32))
-    }
-
-    fn flush(&mut self) -> std::io::Result<()> {
-        // Data is sent immediately when write() is called; flushing is not
-        // needed. No-op implementation because [std::io::Write] requires it.
+    fn send(&mut self, buf: &[u8]) -> Result<()> {
+        send(self.fd, buf, MsgFlags::empty()).map_err(Error::ErrSendSocket)?;
         Ok(())
     }
-}
 
-/// This is the primary way to interact with a Netlink interface. It provides
-/// methods to read and write messages, and buffers all the underlying byte
-/// reads.
-///
-/// For example:
-///
-/// ```rust
-/// use std::error::Error;
-/// use netlink::route::{RouteHeader, RouteMessageType, AF_INET};
-/// use netlink::{NetlinkStream, Flag, NetlinkMessage};
-///
-/// fn main() -> Result<(), Box<dyn Error>> {
-///     let mut conn = NetlinkStream::connect()?;
-///
-///     let rthdr = RouteHeader::builder()
-///         .family(AF_INET)
-///         .build();
-///
-///     let msg = NetlinkMessage::builder()
-///         .typ(RouteMessageType::GetRoute)
-///         .flags(Flag::Request | Flag::Dump)
-///         .append(rthdr)?
-///         .build();
-///
-///     conn.send(msg)?;
-///
-///     for msg in conn.into_iter(){
-///         println!("{msg:?}");
-///     }
-///
-///     Ok(())
-/// }
-/// ```
-pub struct NetlinkStream {
-    sock: NetlinkSocket,
-    reader: BufReader<NetlinkSocket>,
-    writer: BufWriter<NetlinkSocket>,
-    seq: u32,
-    has_remaining_reads: bool,
-}
+    // Datagrams are discarded after the first read, so this peeks at the size
+    // of the next one first to make sure it is never truncated.
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        let flags = MsgFlags::MSG_PEEK | MsgFlags::MSG_TRUNC;
+        let len = recv(self.fd, &mut [], flags).map_err(Error::ErrRecvSocket)?;
 
-impl NetlinkStream {
-    /// Returns a bidirectional stream of Netlink messages.
-    ///
-    /// # Errors
-    ///
-    /// Returns an [`crate::Error`] when a Netlink socket cannot be successfully
-    /// created. This might happen for a variety of reasons.
-    pub fn connect() -> Result<Self> {
-        let sock = NetlinkSocket::connect()?;
-        let writer = BufWriter::new(sock.clone());
-        let reader = BufReader::new(sock.clone());
-        Ok(Self {
-            sock,
-            reader,
-            writer,
-            seq: 0,
-            has_remaining_reads: true,
-        })
+        let mut buf = vec![0u8; len];
+        let len = recv(self.fd, &mut buf, MsgFlags::empty()).map_err(Error::ErrRecvSocket)?;
+        buf.truncate(len);
+        Ok(buf)
     }
 
-    /// Attempt to send a Netlink message.
-    ///
-    /// # Errors
-    ///
-    /// Returns an [`crate::Error`] when writes to socket's underlying file
-    /// descriptor fails.
-    pub fn send(&mut self, mut msg: NetlinkMessage) -> Result<()> {
-        let len = msg.payload.len() + aligned_size_of::<NetlinkHeader>();
-        let header = NetlinkHeader {
-            len: len.try_into().map_err(|_| Error::ErrValueConversion)?,
-            typ: msg.header.typ,
-            flags: msg.header.flags,
-            pid: self.sock.pid,
-            seq: self.seq,
+    fn strict_checking(&self) -> Result<bool> {
+        let mut value: libc::c_int = 0;
+        let mut len: libc::socklen_t = std::mem::size_of::<libc::c_int>()
+            .try_into()
+            .map_err(|_| Error::ErrValueConversion)?;
+
+        // nix doesn't know about this option, so it's read directly. The
+        // pointers are valid for the duration of the call.
+        let res = unsafe {
+            libc::getsockopt(
+                self.fd,
+                libc::SOL_NETLINK,
+                libc::NETLINK_GET_STRICT_CHK,
+                std::ptr::addr_of_mut!(value).cast(),
+                std::ptr::addr_of_mut!(len),
+            )
         };
+        Errno::result(res).map_err(Error::ErrSocketOption)?;
 
-        let mut bytes = serialize_aligned(header)?;
-        bytes.append(&mut msg.payload);
+        Ok(value != 0)
+    }
 
-        self.writer
-            .write_all(&bytes)
-            .map_err(Error::ErrWriteSocket)?;
-        self.writer.flush().map_err(Error::ErrWriteSocket)?;
+    fn set_strict_checking(&mut self, enable: bool) -> Result<()> {
+        let value = libc::c_int::from(enable);
+        let len: libc::socklen_t = std::mem::size_of::<libc::c_int>()
+            .try_into()
+            .map_err(|_| Error::ErrValueConversion)?;
+
+        // See strict_checking
+        let res = unsafe {
+            libc::setsockopt(
+                self.fd,
+                libc::SOL_NETLINK,
+                libc::NETLINK_GET_STRICT_CHK,
+                std::ptr::addr_of!(value).cast(),
+                len,
+            )
+        };
+        Errno::result(res).map_err(Error::ErrSocketOption)?;
 
-        self.seq += 1;
-        self.has_remaining_reads = true;
         Ok(())
     }
 
-    /// Attempt to receive a single Netlink message.
-    ///
-    /// This will return [`None`] if a message header with [`MessageType::Done`]
-    /// is received or after a successful read of a message this is not part
-    /// part of a multipart message sequence.
-    ///
-    /// This will be reset when another message is sent, so the same
-    /// [`NetlinkStream`] can be used.
-    ///
-    /// # Errors
-    ///
-    /// Returns an [`crate::Error`] on failure to read from the underlying
-    /// socket file descriptor.
-    pub fn recv(&mut self) -> Result<Option<NetlinkMessage>> {
-        if !self.has_remaining_reads {
-            return Ok(None);
-        }
-
-        let buf = &mut [0u8; aligned_size_of::<NetlinkHeader>()];
-        self.reader.read(buf).map_err(Error::ErrReadSocket)?;
-        let hdr = deserialize::<NetlinkHeader>(buf).map_err(Error::ErrDeserialize)?;
-
-        if hdr.has_flags(Flag::Multi) {
-            self.has_remaining_reads = true;
-        }
-
-        if hdr.has_type(MessageType::Done) {
-            self.has_remaining_reads = false;
-            return Ok(None);
-        }
-
-        if hdr.len == 0 {
-            let descriptor = hdr.into_descriptor();
-            return Ok(Some(NetlinkMessage::new(descriptor, Vec::new())));
-        }
-
-        let payload_len = hdr.len as usize - aligned_size_of::<NetlinkHeader>();
-        let mut payload = vec![0u8; payload_len];
-        self.reader
-            .read(&mut payload)
-            .map_err(Error::ErrReadSocket)?;
-
-        let descriptor = hdr.into_descriptor();
-        Ok(Some(NetlinkMessage::new(descriptor, payload)))
+    fn meminfo(&self) -> Result<SocketMemInfo> {
+        let mut vars = [0u32; 9];
+        let mut len: libc::socklen_t = std::mem::size_of_val(&vars)
+            .try_into()
+            .map_err(|_| Error::ErrValueConversion)?;
+
+        // See strict_checking. Older kernels report fewer variables, which
+        // leaves the remaining ones zeroed.
+        let res = unsafe {
+            libc::getsockopt(
+                self.fd,
+                libc::SOL_SOCKET,
+                libc::SO_MEMINFO,
+                vars.as_mut_ptr().cast(),
+                std::ptr::addr_of_mut!(len),
+            )
+        };
+        Errno::result(res).map_err(Error::ErrSocketOption)?;
+
+        Ok(SocketMemInfo::from(vars))
     }
 }
 
-impl Iterator for NetlinkStream {
-    type Item = Result<NetlinkMessage>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.recv().transpose()
+impl Drop for NetlinkSocket {
+    fn drop(&mut self) {
+        // Nothing sensible can be done if this fails
+        let _ = close(self.fd);
     }
 }
@@ -0,0 +1,110 @@
+use nix::errno::Errno;
+use std::collections::BTreeMap;
+
+/// Counters of the traffic on a [`crate::NetlinkStream`], since it was
+/// created. See [`crate::NetlinkStream::stats`].
+///
+/// These are maintained by the [`crate::NetlinkCodec`], so they count what was
+/// exchanged with the kernel regardless of how the stream is used.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Stats {
+    /// Requests sent
+    pub messages_sent: u64,
+    /// Bytes sent, including headers and padding
+    pub bytes_sent: u64,
+    /// Datagrams received
+    pub datagrams_received: u64,
+    /// Messages received, including ACKs, errors and `NLMSG_DONE`
+    pub messages_received: u64,
+    /// Bytes received, including headers and padding
+    pub bytes_received: u64,
+    /// Multipart responses terminated by `NLMSG_DONE`
+    pub dumps_completed: u64,
+    /// Multipart responses that were marked with `NLM_F_DUMP_INTR`, because
+    /// the kernel's table changed while it was dumped
+    pub dumps_interrupted: u64,
+    /// ACKs received, which are error messages with errno 0
+    pub acks: u64,
+    /// Errors reported by the kernel, keyed by errno. See
+    /// [`Stats::error_count`].
+    pub errors_by_errno: BTreeMap<i32, u64>,
+    /// `NLMSG_OVERRUN` messages, and reads that failed with `ENOBUFS` because
+    /// the socket's receive buffer overflowed and messages were dropped
+    pub overruns: u64,
+    /// Datagrams that ended in the middle of a message
+    pub truncations: u64,
+    /// Messages discarded because they belong to a response that was
+    /// abandoned after an error. See [`crate::NetlinkCodec::abandon`].
+    pub stale_messages: u64,
+}
+
+impl Stats {
+    /// Number of errors the kernel reported with the given errno.
+    #[must_use]
+    pub fn error_count(&self, errno: Errno) -> u64 {
+        self.errors_by_errno
+            .get(&(errno as i32))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Total number of errors the kernel reported.
+    #[must_use]
+    pub fn errors(&self) -> u64 {
+        self.errors_by_errno.values().sum()
+    }
+
+    pub(crate) fn record_error(&mut self, code: i32) {
+        if code == 0 {
+            self.acks += 1;
+        } else {
+            *self.errors_by_errno.entry(code.wrapping_neg()).or_default() += 1;
+        }
+    }
+}
+
+/// Memory usage of a socket, as reported by the `SO_MEMINFO` socket option.
+/// See [`crate::Transport::meminfo`].
+///
+/// A receive queue that approaches its buffer size means the application
+/// isn't reading fast enough, and the kernel will soon start dropping
+/// messages.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct SocketMemInfo {
+    /// Bytes queued in the receive buffer
+    pub rmem_alloc: u32,
+    /// Size of the receive buffer
+    pub rcvbuf: u32,
+    /// Bytes queued in the send buffer
+    pub wmem_alloc: u32,
+    /// Size of the send buffer
+    pub sndbuf: u32,
+    /// Memory reserved for future use by the socket
+    pub fwd_alloc: u32,
+    /// Bytes queued to be sent
+    pub wmem_queued: u32,
+    /// Memory used for socket options
+    pub optmem: u32,
+    /// Bytes in the backlog queue
+    pub backlog: u32,
+    /// Packets dropped because the receive buffer was full
+    pub drops: u32,
+}
+
+impl From<[u32; 9]> for SocketMemInfo {
+    fn from(vars: [u32; 9]) -> Self {
+        let [rmem_alloc, rcvbuf, wmem_alloc, sndbuf, fwd_alloc, wmem_queued, optmem, backlog, drops] =
+            vars;
+        Self {
+            rmem_alloc,
+            rcvbuf,
+            wmem_alloc,
+            sndbuf,
+            fwd_alloc,
+            wmem_queued,
+            optmem,
+            backlog,
+            drops,
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use crate::codec::{NetlinkCodec, NetlinkEvent};
+use crate::core::dump::Dump;
+use crate::core::trace::RequestSpan;
+use crate::request::NetlinkRequest;
+use crate::route::Capabilities;
+use crate::socket::NetlinkSocket;
+use crate::stats::Stats;
+use crate::transport::Transport;
+use crate::types::NetlinkMessage;
+use crate::{Error, Result};
+use nix::errno::Errno;
+
+/// This is the primary way to interact with a Netlink interface. It provides
+/// methods to read and write messages, and drives a [`NetlinkCodec`] with the
+/// datagrams sent to and received from the socket.
+///
+/// For example:
+///
+/// ```rust
+/// use std::error::Error;
+/// use netlink::route::route::{RouteMessage, RouteMessageType};
+/// use netlink::route::AF_INET;
+/// use netlink::{Flags, GetFlags, NetlinkMessage, NetlinkStream};
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let mut conn = NetlinkStream::connect()?;
+///
+///     let rthdr = RouteMessage::builder()
+///         .family(AF_INET)
+///         .build()?;
+///
+///     let msg = NetlinkMessage::builder()
+///         .typ(RouteMessageType::GetRoute)
+///         .flags(Flags::REQUEST | GetFlags::DUMP)
+///         .append(rthdr)?
+///         .build();
+///
+///     conn.send(msg)?;
+///
+///     for msg in conn.into_iter() {
+///         println!("{msg:?}");
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// By default this talks to the kernel over a [`NetlinkSocket`], but it can
+/// carry messages over any other [`Transport`]:
+///
+/// ```rust
+/// use netlink::route::link::Link;
+/// use netlink::testing::FakeKernel;
+/// use netlink::NetlinkStream;
+///
+/// # fn main() -> netlink::Result<()> {
+/// let kernel = FakeKernel::new().with_link(Link {
+///     index: 1,
+///     name: Some("lo".to_string()),
+///     ..Default::default()
+/// });
+///
+/// let mut conn = NetlinkStream::new(kernel);
+/// let links = conn.list_links()?;
+///
+/// assert_eq!(links[0].name.as_deref(), Some("lo"));
+/// assert_eq!(conn.get_ref().requests().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct NetlinkStream<T: Transport = NetlinkSocket> {
+    transport: T,
+    codec: NetlinkCodec,
+    pub(crate) capabilities: Option<Capabilities>,
+}
+
+impl NetlinkStream<NetlinkSocket> {
+    /// Returns a bidirectional stream of Netlink messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] when a Netlink socket cannot be successfully
+    /// created. This might happen for a variety of reasons.
+    pub fn connect() -> Result<Self> {
+        NetlinkSocket::connect().map(Self::new)
+    }
+}
+
+impl<T: Transport> NetlinkStream<T> {
+    /// Returns a bidirectional stream of Netlink messages that are carried by
+    /// `transport`.
+    pub fn new(transport: T) -> Self {
+        let codec = NetlinkCodec::new(transport.pid());
+        Self {
+            transport,
+            codec,
+            capabilities: None,
+        }
+    }
+
+    /// Get a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get a mutable reference to the underlying transport.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Consume the stream and return the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Attempt to send a Netlink message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] when writes to socket's underlying file
+    /// descriptor fails.
+    pub fn send(&mut self, msg: NetlinkMessage) -> Result<()> {
+        let bytes = self.codec.encode(msg)?;
+        self.transport.send(&bytes)
+    }
+
+    /// Attempt to receive a single Netlink message.
+    ///
+    /// This will return [`None`] if a message header with [`MessageType::Done`]
+    /// is received or after a successful read of a message this is not part
+    /// part of a multipart message sequence. If the request asked for an ACK,
+    /// the ACK or error that follows such a message is read as well.
+    ///
+    /// This will be reset when another message is sent, so the same
+    /// [`NetlinkStream`] can be used.
+    ///
+    /// [`MessageType::Done`]: crate::MessageType::Done
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure to read from the underlying
+    /// socket file descriptor, and [`Error::ErrKernel`] if the kernel ended a
+    /// dump with an error, so that the messages received are incomplete.
+    pub fn recv(&mut self) -> Result<Option<NetlinkMessage>> {
+        loop {
+            match self.codec.poll_event() {
+                Some(NetlinkEvent::Message(msg)) => return Ok(Some(msg)),
+                Some(NetlinkEvent::Done) => return Ok(None),
+                Some(NetlinkEvent::DumpFailed(errno)) => return Err(Error::ErrKernel(errno)),
+                None if !self.codec.has_remaining_reads() => return Ok(None),
+                None => {
+                    let datagram = self.transport.recv().map_err(|err| {
+                        // The receive buffer overflowed, so messages were lost
+                        if matches!(err, Error::ErrRecvSocket(Errno::ENOBUFS)) {
+                            self.codec.stats_mut().overruns += 1;
+                        }
+                        err
+                    })?;
+                    self.codec.decode(&datagram)?;
+                }
+            }
+        }
+    }
+
+    /// Counters of the traffic on this stream, like the number of messages
+    /// sent and received, dumps that were interrupted and errors by errno. See
+    /// also [`Transport::meminfo`], which reports the socket's drops.
+    pub fn stats(&self) -> &Stats {
+        self.codec.stats()
+    }
+
+    /// Send a request and decode every message of the response.
+    ///
+    /// The whole response is read before it is decoded, so the stream is
+    /// ready for the next request even if decoding fails. ACKs are consumed,
+    /// and an error reported by the kernel is turned into an [`crate::Error`]
+    /// by [`NetlinkRequest::map_error`].
+    ///
+    /// With the `tracing` feature, every request is wrapped in a
+    /// `netlink_request` span that records its type, sequence number, flags,
+    /// byte counts, number of response messages, errno and latency. The span
+    /// is entered while the request is sent and its response is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the request cannot be sent, the kernel
+    /// rejects it, or the response is malformed.
+    pub fn execute<R: NetlinkRequest>(&mut self, req: &R) -> Result<Vec<R::Response>> {
+        let mut span = self.send_request(req)?;
+        let _entered = span.enter();
+
+        let mut messages = vec![];
+        while let Some(msg) = self.recv_response(req, &mut span)? {
+            span.received(&msg);
+            messages.push(msg);
+        }
+
+        let mut responses = vec![];
+        for msg in messages {
+            match msg.error_code()? {
+                Some(0) => {}
+                Some(code) => {
+                    let errno = Errno::from_i32(code.wrapping_neg());
+                    span.failed(errno);
+                    return Err(req.map_error(errno));
+                }
+                None => responses.push(req.deserialize(&msg)?),
+            }
+        }
+
+        Ok(responses)
+    }
+
+    /// Send a request and return an iterator that decodes the messages of the
+    /// response as they arrive, instead of collecting them first like
+    /// [`NetlinkStream::execute`]. This keeps the memory use flat when
+    /// dumping large tables.
+    ///
+    /// ```rust
+    /// use netlink::route::route::ListRoutes;
+    /// use netlink::NetlinkStream;
+    ///
+    /// # fn main() -> netlink::Result<()> {
+    /// let mut conn = NetlinkStream::connect()?;
+    ///
+    /// for route in conn.dump(ListRoutes::default())? {
+    ///     println!("{:?}", route?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the request cannot be sent. Errors
+    /// while reading the response are returned by the iterator.
+    pub fn dump<R: NetlinkRequest>(&mut self, req: R) -> Result<Dump<'_, R, T>> {
+        let span = self.send_request(&req)?;
+        Ok(Dump::new(self, req, span))
+    }
+
+    /// Receive the next message of the response to `req`. A dump that the
+    /// kernel ended with an error is reported like any other error of the
+    /// request, rather than as the end of a truncated response. If reading
+    /// fails, the rest of the response is abandoned so that it isn't read as
+    /// the response to the next request.
+    pub(crate) fn recv_response<R: NetlinkRequest>(
+        &mut self,
+        req: &R,
+        span: &mut RequestSpan,
+    ) -> Result<Option<NetlinkMessage>> {
+        match self.recv() {
+            Err(Error::ErrKernel(errno)) => {
+                span.failed(errno);
+                Err(req.map_error(errno))
+            }
+            Err(err) => {
+                self.codec.abandon();
+                Err(err)
+            }
+            res => res,
+        }
+    }
+
+    fn send_request<R: NetlinkRequest>(&mut self, req: &R) -> Result<RequestSpan> {
+        let msg = req.to_message()?;
+        let mut span = RequestSpan::new(&msg, self.codec.seq());
+        let _entered = span.enter();
+
+        let bytes = self.codec.encode(msg)?;
+        span.sent(bytes.len());
+        self.transport.send(&bytes)?;
+
+        Ok(span)
+    }
+}
+
+impl<T: Transport> Iterator for NetlinkStream<T> {
+    type Item = Result<NetlinkMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::serialize_message;
+    use crate::route::route::{serialize_route, Route, RouteMessageType};
+    use crate::route::{IpPrefix, OperationErrorKind};
+    use crate::testing::FakeKernel;
+    use crate::{Flags, MessageType};
+    use std::collections::VecDeque;
+
+    // Answers requests with canned datagrams, or failed reads
+    struct Scripted {
+        datagrams: VecDeque<Result<Vec<u8>>>,
+    }
+
+    impl Transport for Scripted {
+        fn pid(&self) -> u32 {
+            0
+        }
+
+        fn send(&mut self, _buf: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>> {
+            self.datagrams
+                .pop_front()
+                .unwrap_or(Err(Error::ErrRecvSocket(Errno::EAGAIN)))
+        }
+    }
+
+    fn route_message(dest: &str, seq: u32) -> Result<Vec<u8>> {
+        let route = Route::builder().dest(dest.parse::<IpPrefix>()?).build()?;
+        let typ = RouteMessageType::NewRoute.into();
+        serialize_message(typ, Flags::MULTI.bits(), seq, 0, serialize_route(&route)?)
+    }
+
+    fn done_message(status: i32, seq: u32) -> Result<Vec<u8>> {
+        let typ = MessageType::Done.into();
+        serialize_message(
+            typ,
+            Flags::MULTI.bits(),
+            seq,
+            0,
+            status.to_le_bytes().to_vec(),
+        )
+    }
+
+    // One route, then an NLMSG_DONE reporting that the dump failed
+    fn failed_dump() -> Result<Scripted> {
+        let mut datagram = route_message("10.0.0.0/8", 0)?;
+        datagram.append(&mut done_message(-(Errno::EBUSY as i32), 0)?);
+
+        Ok(Scripted {
+            datagrams: VecDeque::from([Ok(datagram)]),
+        })
+    }
+
+    #[test]
+    fn execute_reports_failed_dump() -> Result<()> {
+        let mut conn = NetlinkStream::new(failed_dump()?);
+
+        let err = conn.list_routes().unwrap_err();
+        assert_eq!(err.errno(), Some(Errno::EBUSY));
+        assert_eq!(err.operation_kind(), Some(OperationErrorKind::Busy));
+        Ok(())
+    }
+
+    #[test]
+    fn dump_yields_failed_dump() -> Result<()> {
+        let mut conn = NetlinkStream::new(failed_dump()?);
+
+        let mut routes = conn.routes()?;
+        assert!(routes.next().is_some_and(|route| route.is_ok()));
+
+        let err = routes.next().and_then(Result::err);
+        assert_eq!(err.and_then(|err| err.errno()), Some(Errno::EBUSY));
+        assert!(routes.next().is_none());
+        Ok(())
+    }
+
+    fn dests(routes: &[Route]) -> Vec<String> {
+        routes
+            .iter()
+            .filter_map(|route| route.dest.map(|dest| dest.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn failed_read_abandons_dump() -> Result<()> {
+        let mut rest = route_message("10.1.0.0/16", 0)?;
+        rest.append(&mut done_message(0, 0)?);
+        let mut next = route_message("10.2.0.0/16", 1)?;
+        next.append(&mut done_message(0, 1)?);
+
+        let mut conn = NetlinkStream::new(Scripted {
+            datagrams: VecDeque::from([
+                route_message("10.0.0.0/8", 0),
+                Err(Error::ErrRecvSocket(Errno::EINTR)),
+                Ok(rest),
+                Ok(next),
+            ]),
+        });
+
+        let mut routes = conn.routes()?;
+        assert!(routes.next().is_some_and(|route| route.is_ok()));
+        assert!(routes.next().is_some_and(|route| route.is_err()));
+        assert!(routes.next().is_none());
+        drop(routes);
+
+        assert_eq!(dests(&conn.list_routes()?), ["10.2.0.0/16"]);
+        assert_eq!(conn.stats().stale_messages, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_error_drains_dump() -> Result<()> {
+        let mut first = route_message("10.0.0.0/8", 0)?;
+        let typ = MessageType::Error.into();
+        first.append(&mut serialize_message(
+            typ,
+            Flags::MULTI.bits(),
+            0,
+            0,
+            vec![0; 2],
+        )?);
+        let mut rest = route_message("10.1.0.0/16", 0)?;
+        rest.append(&mut done_message(0, 0)?);
+        let mut next = route_message("10.2.0.0/16", 1)?;
+        next.append(&mut done_message(0, 1)?);
+
+        let mut conn = NetlinkStream::new(Scripted {
+            datagrams: VecDeque::from([Ok(first), Ok(rest), Ok(next)]),
+        });
+
+        let mut routes = conn.routes()?;
+        assert!(routes.next().is_some_and(|route| route.is_ok()));
+        assert!(routes.next().is_some_and(|route| route.is_err()));
+        assert!(routes.next().is_none());
+        drop(routes);
+
+        assert_eq!(dests(&conn.list_routes()?), ["10.2.0.0/16"]);
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_error_code_is_reported() -> Result<()> {
+        let kernel = FakeKernel::new().with_error_code(RouteMessageType::GetLink, i32::MIN);
+        let mut conn = NetlinkStream::new(kernel);
+
+        let err = conn.list_links().unwrap_err();
+        assert_eq!(err.errno(), Some(Errno::UnknownErrno));
+
+        let err = conn.links()?.next().transpose().unwrap_err();
+        assert_eq!(err.errno(), Some(Errno::UnknownErrno));
+
+        assert_eq!(conn.stats().errors(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::types::NetlinkMessage;
+use nix::errno::Errno;
+
+/// A span around a request sent by [`crate::NetlinkStream::execute`], which
+/// records what was exchanged with the kernel. This does nothing unless the
+/// `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+pub(crate) struct RequestSpan {
+    span: tracing::Span,
+    start: std::time::Instant,
+    parts: usize,
+    bytes_received: usize,
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct RequestSpan;
+
+/// Guard returned by [`RequestSpan::enter`] without the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+#[cfg(feature = "tracing")]
+impl RequestSpan {
+    pub(crate) fn new(msg: &NetlinkMessage, seq: u32) -> Self {
+        let typ = msg.header.typ;
+        let span = tracing::debug_span!(
+            "netlink_request",
+            typ = crate::pretty::message_type_name(typ).unwrap_or("unknown"),
+            typ.raw = typ,
+            seq,
+            flags = format_args!("{:#x}", msg.header.flags),
+            bytes_sent = tracing::field::Empty,
+            bytes_received = tracing::field::Empty,
+            parts = tracing::field::Empty,
+            errno = tracing::field::Empty,
+            latency_us = tracing::field::Empty,
+        );
+
+        Self {
+            span,
+            start: std::time::Instant::now(),
+            parts: 0,
+            bytes_received: 0,
+        }
+    }
+
+    /// Enter the span until the guard is dropped, so that what is logged
+    /// while working on the request is attributed to it.
+    pub(crate) fn enter(&self) -> tracing::span::EnteredSpan {
+        self.span.clone().entered()
+    }
+
+    pub(crate) fn sent(&mut self, len: usize) {
+        self.span.record("bytes_sent", len);
+    }
+
+    pub(crate) fn received(&mut self, msg: &NetlinkMessage) {
+        self.parts += 1;
+        self.bytes_received += message_size(msg);
+    }
+
+    pub(crate) fn failed(&mut self, errno: Errno) {
+        self.span.record("errno", tracing::field::debug(errno));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for RequestSpan {
+    fn drop(&mut self) {
+        let latency = self.start.elapsed().as_micros();
+        self.span.record("bytes_received", self.bytes_received);
+        self.span.record("parts", self.parts);
+        self.span
+            .record("latency_us", u64::try_from(latency).unwrap_or(u64::MAX));
+        tracing::debug!(parent: &self.span, "netlink request finished");
+    }
+}
+
+// Keeps the same interface, so callers don't need to care about the feature
+#[cfg(not(feature = "tracing"))]
+#[allow(clippy::unused_self)]
+impl RequestSpan {
+    pub(crate) fn new(_msg: &NetlinkMessage, _seq: u32) -> Self {
+        Self
+    }
+
+    pub(crate) fn enter(&self) -> Entered {
+        Entered
+    }
+
+    pub(crate) fn sent(&mut self, _len: usize) {}
+
+    pub(crate) fn received(&mut self, _msg: &NetlinkMessage) {}
+
+    pub(crate) fn failed(&mut self, _errno: Errno) {}
+}
+
+// Size of the message on the wire, including its header and padding
+#[cfg(feature = "tracing")]
+fn message_size(msg: &NetlinkMessage) -> usize {
+    use crate::bytes::{aligned_size, aligned_size_of};
+    use crate::types::NetlinkHeader;
+
+    aligned_size_of::<NetlinkHeader>() + aligned_size(msg.payload.len())
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::route::route::Route;
+    use crate::route::IpPrefix;
+    use crate::testing::FakeKernel;
+    use crate::transport::Transport;
+    use crate::{NetlinkStream, Result};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    // Records which events were logged while a span was entered, by the
+    // name of their only field
+    #[derive(Clone, Default)]
+    struct Recorder {
+        next_id: Arc<AtomicU64>,
+        depth: Arc<AtomicU64>,
+        events: Arc<Mutex<Vec<(&'static str, bool)>>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if let Some(field) = event.fields().next() {
+                let in_span = self.depth.load(Ordering::SeqCst) > 0;
+                self.events.lock().unwrap().push((field.name(), in_span));
+            }
+        }
+
+        fn enter(&self, _span: &Id) {
+            self.depth.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Logs every datagram it carries
+    struct Logging(FakeKernel);
+
+    impl Transport for Logging {
+        fn pid(&self) -> u32 {
+            0
+        }
+
+        fn send(&mut self, buf: &[u8]) -> Result<()> {
+            tracing::info!(transport = buf.len());
+            self.0.send(buf)
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>> {
+            tracing::info!(transport = 0);
+            self.0.recv()
+        }
+    }
+
+    fn logging_kernel() -> Result<Logging> {
+        let route = Route::builder()
+            .dest("10.0.0.0/8".parse::<IpPrefix>()?)
+            .build()?;
+        Ok(Logging(FakeKernel::new().with_route(route)))
+    }
+
+    #[test]
+    fn execute_runs_in_span() -> Result<()> {
+        let recorder = Recorder::default();
+        let mut conn = NetlinkStream::new(logging_kernel()?);
+        tracing::subscriber::with_default(recorder.clone(), || conn.list_routes())?;
+
+        let events = recorder.events.lock().unwrap();
+        assert!(events.contains(&("transport", true)));
+        assert!(!events.contains(&("transport", false)));
+        Ok(())
+    }
+
+    #[test]
+    fn dump_runs_in_span_between_items() -> Result<()> {
+        let recorder = Recorder::default();
+        let mut conn = NetlinkStream::new(logging_kernel()?);
+        tracing::subscriber::with_default(recorder.clone(), || -> Result<()> {
+            for route in conn.routes()? {
+                route?;
+                tracing::info!(caller = true);
+            }
+            Ok(())
+        })?;
+
+        let events = recorder.events.lock().unwrap();
+        assert!(events.contains(&("transport", true)));
+        assert!(!events.contains(&("transport", false)));
+        assert_eq!(events.iter().filter(|event| event.0 == "caller").count(), 1);
+        assert!(!events.contains(&("caller", true)));
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::stats::SocketMemInfo;
+use crate::{Error, Result};
+use nix::errno::Errno;
+
+/// Carries Netlink datagrams between a [`NetlinkStream`] and the kernel.
+///
+/// [`NetlinkSocket`] is the implementation used by
+/// [`NetlinkStream::connect`]. Other implementations make it possible to talk
+/// to something other than a real socket, like the
+/// [`FakeKernel`](crate::testing::FakeKernel) used in tests.
+///
+/// [`NetlinkStream`]: crate::NetlinkStream
+/// [`NetlinkStream::connect`]: crate::NetlinkStream::connect
+/// [`NetlinkSocket`]: crate::NetlinkSocket
+pub trait Transport {
+    /// The port ID written into the header of outgoing messages.
+    fn pid(&self) -> u32;
+
+    /// Send a single datagram, which contains one or more Netlink messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the datagram could not be sent.
+    fn send(&mut self, buf: &[u8]) -> Result<()>;
+
+    /// Receive a single datagram, which contains one or more Netlink messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the datagram could not be received.
+    fn recv(&mut self) -> Result<Vec<u8>>;
+
+    /// Whether the kernel strictly validates the headers and attributes of
+    /// GET requests, see `NETLINK_GET_STRICT_CHK` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the transport doesn't support the
+    /// option, which is the default.
+    fn strict_checking(&self) -> Result<bool> {
+        Err(Error::ErrSocketOption(Errno::ENOPROTOOPT))
+    }
+
+    /// Enable or disable strict checking of GET requests. See
+    /// [`Transport::strict_checking`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the transport doesn't support the
+    /// option, which is the default.
+    fn set_strict_checking(&mut self, enable: bool) -> Result<()> {
+        let _ = enable;
+        Err(Error::ErrSocketOption(Errno::ENOPROTOOPT))
+    }
+
+    /// Memory usage of the socket, including the number of messages the
+    /// kernel dropped because the receive buffer was full. See `SO_MEMINFO`
+    /// in the [socket(7) manpage](https://man7.org/linux/man-pages/man7/socket.7.html).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if the transport doesn't support the
+    /// option, which is the default.
+    fn meminfo(&self) -> Result<SocketMemInfo> {
+        Err(Error::ErrSocketOption(Errno::ENOPROTOOPT))
+    }
+}
@@ -1,4 +1,5 @@
 use crate::bytes::serialize_aligned;
+use crate::flags::FlagSet;
 use crate::Result;
 use serde::{Deserialize, Serialize};
 
@@ -22,72 +23,50 @@ impl From<MessageType> for u16 {
     }
 }
 
-/// Flags for configuring Netlink messages.
-pub enum Flag {
-    /// Must be set of all request messages
-    Request,
-    /// This message is part of a multipart message terminated by a message with
-    /// type [MessageType::Done]
-    Multi,
-    /// Acknowledgement of success
-    Ack,
-    /// Return the complete table instead of a single entry
-    Root,
-    /// Return all entries matching the criteria passed in the message content
-    Match,
-    /// Return an atomic snapshot of the table. Required the `CAP_NET_ADMIN`
-    /// capability or an effective UID of 0.
-    Atomic,
-    /// Return all entries. Convenience macro, equivalent to [Root] OR [Match].
-    Dump,
-    /// Replace an existing object
-    Replace,
-    /// Don't replace if the object already exists (see [Flag::Replace])
-    Excl,
-    /// Create object if it doesn't already exist
-    Create,
-    /// Add to the end of the object list
-    Append,
-}
-
-impl From<Flag> for u16 {
-    fn from(flag: Flag) -> Self {
-        match flag {
-            Flag::Request => 0x1,
-            Flag::Multi => 0x2,
-            Flag::Ack => 0x4,
-            Flag::Root => 0x100,
-            Flag::Match => 0x200,
-            Flag::Atomic => 0x400,
-            Flag::Dump => 0x100 | 0x200,
-            Flag::Replace => 0x100,
-            Flag::Excl => 0x200,
-            Flag::Create => 0x400,
-            Flag::Append => 0x800,
-        }
-    }
-}
-
-impl std::ops::BitOr for Flag {
-    type Output = u16;
-
-    fn bitor(self, rhs: Self) -> Self::Output {
-        let lhs: u16 = self.into();
-        let rhs: u16 = rhs.into();
-        lhs | rhs
-    }
-}
-
 /// Descriptor of a Netlink header.
 ///
 /// A complete
 /// [`nlmsghdr`](https://man7.org/linux/man-pages/man7/netlink.7.html) has
-/// additional `len`, `seq` and `pid` fields. These are calculated by
-/// [`NetlinkStream`] when the message is sent.
+/// an additional `len` field, which is calculated when the message is
+/// serialized. The `seq` and `pid` fields are filled in by
+/// [`crate::NetlinkStream`] when the message is sent, and are only meaningful
+/// for received messages.
 #[derive(PartialEq, Clone, Debug, Default)]
 pub struct NetlinkHeaderDescriptor {
     pub(crate) typ: u16,
     pub(crate) flags: u16,
+    pub(crate) seq: u32,
+    pub(crate) pid: u32,
+}
+
+impl NetlinkHeaderDescriptor {
+    /// See `nlmsg_type` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    #[must_use]
+    pub fn typ(&self) -> u16 {
+        self.typ
+    }
+
+    /// See `nlmsg_flags` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    #[must_use]
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// See `nlmsg_seq` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    #[must_use]
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// See `nlmsg_pid` in the [netlink(7)
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    #[must_use]
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
 }
 
 #[repr(C)]
@@ -105,6 +84,8 @@ impl NetlinkHeader {
         NetlinkHeaderDescriptor {
             typ: self.typ,
             flags: self.flags,
+            seq: self.seq,
+            pid: self.pid,
         }
     }
 
@@ -118,10 +99,10 @@ impl NetlinkHeader {
 
     /// Check if the header has a certain flag set.
     ///
-    /// See ///
+    /// See
     /// [`nlmsg_flags`](https://man7.org/linux/man-pages/man7/netlink.7.html).
-    pub fn has_flags(&self, flags: Flag) -> bool {
-        let flags: u16 = flags.into();
+    pub fn has_flags<F: FlagSet>(&self, flags: F) -> bool {
+        let flags = flags.bits();
         self.flags & flags == flags
     }
 }
@@ -174,10 +155,12 @@ impl NetlinkMessageBuilder {
     }
 
     /// See `nlmsg_flags` in the [netlink(7)
-    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html).
+    /// manpage](https://man7.org/linux/man-pages/man7/netlink.7.html). This
+    /// takes [`crate::Flags`], optionally combined with the modifiers of one
+    /// kind of request, like `Flags::REQUEST | GetFlags::DUMP`.
     #[must_use]
-    pub fn flags(mut self, flags: u16) -> Self {
-        self.flags = flags;
+    pub fn flags<F: FlagSet>(mut self, flags: F) -> Self {
+        self.flags = flags.bits();
         self
     }
 
@@ -200,6 +183,7 @@ impl NetlinkMessageBuilder {
             header: NetlinkHeaderDescriptor {
                 typ: self.typ,
                 flags: self.flags,
+                ..Default::default()
             },
             payload: self.payload,
         }
@@ -11,12 +11,23 @@ pub enum Error {
     ErrBindSocket(nix::errno::Errno),
     #[error("failed to send to socket with errno {0}")]
     ErrSendSocket(nix::errno::Errno),
+    #[error("failed to access socket option with errno {0}")]
+    ErrSocketOption(nix::errno::Errno),
     #[error("failed to write to socket with error {0}")]
     ErrWriteSocket(std::io::Error),
     #[error("failed to read from socket with error {0}")]
     ErrReadSocket(std::io::Error),
     #[error("failed to recv from socket with errno {0}")]
     ErrRecvSocket(nix::errno::Errno),
+    #[error("kernel rejected the request with errno {0}")]
+    ErrKernel(nix::errno::Errno),
+    /// An error the kernel reported for a request of a protocol, which
+    /// classifies the errno, like [`crate::route::OperationError`]
+    #[error("{error}")]
+    ErrOperation {
+        errno: nix::errno::Errno,
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
     #[error("failed to if_nametoindex with errno {0}")]
     ErrNameToIndex(nix::errno::Errno),
     #[error("socket gather vector had no segments")]
@@ -37,6 +48,34 @@ pub enum Error {
     ErrDeserializeRouteAttr(crate::route::route::RouteAttrType),
     #[error("failued to convert value")]
     ErrValueConversion,
+    #[error("prefix length {0} is longer than the address")]
+    ErrPrefixLength(u8),
+    #[error("failed to parse prefix {0:?}")]
+    ErrParsePrefix(String),
+    #[error("MPLS label {0} is longer than 20 bits")]
+    ErrMplsLabel(u32),
+    #[error("next hop weight {0} is not between 1 and 256")]
+    ErrNexthopWeight(u16),
+    #[error("{0} segments don't fit in a segment routing header")]
+    ErrTooManySegments(usize),
+    #[error("failed to access recording with error {0}")]
+    ErrRecording(std::io::Error),
+    #[error("recording is malformed on line {0}")]
+    ErrMalformedRecording(usize),
+    #[error("sent datagram does not match the recording")]
+    ErrReplayMismatch,
+    #[error("failed to access capture with error {0}")]
+    ErrCapture(std::io::Error),
+    #[error("capture is not in the pcap format")]
+    ErrMalformedCapture,
+    #[error("capture is in the pcapng format, convert it with `editcap -F pcap <in> <out>`")]
+    ErrPcapngCapture,
+    #[error("capture has unsupported link-layer header type {0}")]
+    ErrUnsupportedLinkType(u32),
+    #[error("kernel sent no {0} in response to the request")]
+    ErrNoResponse(&'static str),
+    #[error("addresses {0} and {1} are not of the same family")]
+    ErrFamilyMismatch(std::net::IpAddr, std::net::IpAddr),
 }
 
 impl From<derive_builder::UninitializedFieldError> for Error {
@@ -44,3 +83,16 @@ impl From<derive_builder::UninitializedFieldError> for Error {
         Self::ErrBuild(err)
     }
 }
+
+impl Error {
+    /// The errno the kernel rejected a request with, if this error was
+    /// reported by the kernel.
+    #[must_use]
+    pub fn errno(&self) -> Option<nix::errno::Errno> {
+        match self {
+            Self::ErrKernel(errno) => Some(*errno),
+            Self::ErrOperation { errno, .. } => Some(*errno),
+            _ => None,
+        }
+    }
+}
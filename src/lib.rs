@@ -18,6 +18,9 @@ pub use crate::core::*;
 // rnetlink(7) implementation
 pub mod route;
 
+// In-memory implementations for hermetic tests
+pub mod testing;
+
 pub mod error;
 pub use error::*;
 
@@ -0,0 +1,195 @@
+use super::InterfaceAddrMessage;
+use crate::bytes::SliceReader;
+use crate::bytes::{deserialize_ascii, deserialize_ip_addr, deserialize_u32, deserialize_u8};
+use crate::route::route::RouteScope;
+use crate::route::AddressFamily;
+use crate::Result;
+use serde::Serialize;
+use std::net::IpAddr;
+use std::time::Duration;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+const IFA_BROADCAST: u16 = 4;
+const IFA_ANYCAST: u16 = 5;
+const IFA_CACHEINFO: u16 = 6;
+const IFA_MULTICAST: u16 = 7;
+const IFA_FLAGS: u16 = 8;
+const IFA_RT_PRIORITY: u16 = 9;
+const IFA_PROTO: u16 = 11;
+
+/// Lifetime the kernel reports for addresses that don't expire
+const INFINITY_LIFE_TIME: u32 = u32::MAX;
+
+/// An address of an interface, as described by an `RTM_*ADDR` message, like
+/// a line of `ip address show`.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+pub struct Address {
+    pub family: AddressFamily,
+    pub prefix_len: u8,
+    /// `IFA_F_*` flags, like `IFA_F_SECONDARY`. Taken from `IFA_FLAGS` when
+    /// it's present, since the newer flags don't fit in the header.
+    pub flags: u32,
+    pub scope: RouteScope,
+    /// Index of the interface the address is on
+    pub index: u32,
+    /// Address of the interface, or of the other end of a point-to-point
+    /// link when it differs from `local`
+    pub address: Option<IpAddr>,
+    /// Local address of the interface
+    pub local: Option<IpAddr>,
+    /// Name of the interface, or the alias like `eth0:1` the address was
+    /// added with
+    pub label: Option<String>,
+    pub broadcast: Option<IpAddr>,
+    pub anycast: Option<IpAddr>,
+    pub multicast: Option<IpAddr>,
+    /// Time left until the address is deprecated, or `None` if it isn't
+    /// reported or is preferred forever
+    pub preferred_lifetime: Option<Duration>,
+    /// Time left until the address is removed, or `None` if it isn't
+    /// reported or is valid forever
+    pub valid_lifetime: Option<Duration>,
+    /// Metric of the prefix route added with the address
+    pub priority: Option<u32>,
+    /// What added the address, like 3 for the kernel's IPv6 link-local
+    /// addresses
+    pub protocol: Option<u8>,
+}
+
+/// Parse an `ifaddrmsg` header followed by its `IFA_*` attributes. Attributes
+/// this crate doesn't know are skipped.
+pub(crate) fn parse_addr(payload: &[u8]) -> Result<Address> {
+    let mut reader = SliceReader::new(payload);
+    let ifa = reader.read::<InterfaceAddrMessage>()?;
+
+    let mut addr = Address {
+        family: AddressFamily::try_from(ifa.family).unwrap_or_default(),
+        prefix_len: ifa.prefixlen,
+        flags: u32::from(ifa.flags),
+        scope: ifa.scope.into(),
+        index: ifa.index,
+        ..Default::default()
+    };
+
+    while !reader.is_empty() {
+        let (typ, value) = reader.read_attribute()?;
+        match typ {
+            IFA_ADDRESS => addr.address = Some(deserialize_ip_addr(value)?),
+            IFA_LOCAL => addr.local = Some(deserialize_ip_addr(value)?),
+            IFA_LABEL => addr.label = Some(deserialize_ascii(value)),
+            IFA_BROADCAST => addr.broadcast = Some(deserialize_ip_addr(value)?),
+            IFA_ANYCAST => addr.anycast = Some(deserialize_ip_addr(value)?),
+            IFA_MULTICAST => addr.multicast = Some(deserialize_ip_addr(value)?),
+            IFA_CACHEINFO => {
+                // struct ifa_cacheinfo starts with the preferred and valid
+                // lifetimes in seconds
+                let mut info = SliceReader::new(value);
+                addr.preferred_lifetime = lifetime(deserialize_u32(info.take(4)?)?);
+                addr.valid_lifetime = lifetime(deserialize_u32(info.take(4)?)?);
+            }
+            IFA_FLAGS => addr.flags = deserialize_u32(value)?,
+            IFA_RT_PRIORITY => addr.priority = Some(deserialize_u32(value)?),
+            IFA_PROTO => addr.protocol = Some(deserialize_u8(value)?),
+            _ => {}
+        }
+    }
+
+    Ok(addr)
+}
+
+fn lifetime(seconds: u32) -> Option<Duration> {
+    (seconds != INFINITY_LIFE_TIME).then(|| Duration::from_secs(u64::from(seconds)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const IFA_F_SECONDARY: u32 = 0x01;
+    const IFA_F_PERMANENT: u32 = 0x80;
+
+    // ip address add 10.10.0.3/24 brd + dev nltest0 label nltest0:1, added
+    // after 10.10.0.2/24 so it's a secondary address
+    #[rustfmt::skip]
+    const SECONDARY_ADDR: &[u8] = &[
+        0x02, 0x18, 0x81, 0x00, 0x06, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x01, 0x00, 0x0a, 0x0a, 0x00, 0x03,
+        0x08, 0x00, 0x02, 0x00, 0x0a, 0x0a, 0x00, 0x03,
+        0x08, 0x00, 0x04, 0x00, 0x0a, 0x0a, 0x00, 0xff,
+        0x0e, 0x00, 0x03, 0x00, 0x6e, 0x6c, 0x74, 0x65,
+        0x73, 0x74, 0x30, 0x3a, 0x31, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x08, 0x00, 0x81, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x06, 0x00, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xcc, 0xcb, 0x0a, 0x00,
+        0xcc, 0xcb, 0x0a, 0x00,
+    ];
+
+    // The IPv6 link-local address the kernel adds to nltest0
+    #[rustfmt::skip]
+    const LINK_LOCAL_ADDR: &[u8] = &[
+        0x0a, 0x40, 0x80, 0xfd, 0x06, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x01, 0x00, 0xfe, 0x80, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x50, 0xb1, 0x10, 0xff,
+        0xfe, 0x6a, 0x54, 0x99, 0x14, 0x00, 0x06, 0x00,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xb0, 0x2e, 0x0a, 0x00, 0xb0, 0x2e, 0x0a, 0x00,
+        0x08, 0x00, 0x08, 0x00, 0x80, 0x00, 0x00, 0x00,
+        0x05, 0x00, 0x0b, 0x00, 0x03, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn kernel_address_is_parsed() -> Result<()> {
+        let addr = parse_addr(SECONDARY_ADDR)?;
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 10, 0, 3));
+        assert_eq!(
+            addr,
+            Address {
+                family: AddressFamily::Inet,
+                prefix_len: 24,
+                flags: IFA_F_SECONDARY | IFA_F_PERMANENT,
+                scope: RouteScope::Universe,
+                index: 6,
+                address: Some(ip),
+                local: Some(ip),
+                label: Some("nltest0:1".to_owned()),
+                broadcast: Some(IpAddr::V4(Ipv4Addr::new(10, 10, 0, 255))),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn kernel_link_local_address_is_parsed() -> Result<()> {
+        let addr = parse_addr(LINK_LOCAL_ADDR)?;
+        let ip = Ipv6Addr::new(0xfe80, 0, 0, 0, 0x50b1, 0x10ff, 0xfe6a, 0x5499);
+        assert_eq!(
+            addr,
+            Address {
+                family: AddressFamily::Inet6,
+                prefix_len: 64,
+                flags: IFA_F_PERMANENT,
+                scope: RouteScope::Link,
+                index: 6,
+                address: Some(IpAddr::V6(ip)),
+                protocol: Some(3),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lifetimes_are_parsed() -> Result<()> {
+        let mut payload = LINK_LOCAL_ADDR.to_vec();
+        payload[32..40].copy_from_slice(&[0x2c, 0x01, 0x00, 0x00, 0x58, 0x02, 0x00, 0x00]);
+
+        let addr = parse_addr(&payload)?;
+        assert_eq!(addr.preferred_lifetime, Some(Duration::from_mins(5)));
+        assert_eq!(addr.valid_lifetime, Some(Duration::from_mins(10)));
+        Ok(())
+    }
+}
@@ -1,2 +1,8 @@
 pub mod types;
 pub use types::*;
+
+mod address;
+pub use address::*;
+
+mod pretty;
+pub use pretty::*;
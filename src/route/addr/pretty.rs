@@ -0,0 +1,64 @@
+use super::InterfaceAddrMessage;
+use crate::bytes::{deserialize_repr, SliceReader};
+use crate::pretty::{decode_attributes, family_name, AttrKind, AttrSpec, Field, FieldValue};
+use crate::pretty::{FlagKind, MessageDecoder};
+use crate::route::route::RouteMessageType;
+use crate::Result;
+
+/// Decodes `RTM_*ADDR` messages. See [`crate::DecoderRegistry`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AddrDecoder;
+
+#[rustfmt::skip]
+const IFA_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "IFA_ADDRESS", AttrKind::Addr),
+    AttrSpec::new(2, "IFA_LOCAL", AttrKind::Addr),
+    AttrSpec::new(3, "IFA_LABEL", AttrKind::Str),
+    AttrSpec::new(4, "IFA_BROADCAST", AttrKind::Addr),
+    AttrSpec::new(5, "IFA_ANYCAST", AttrKind::Addr),
+    AttrSpec::new(6, "IFA_CACHEINFO", AttrKind::Bytes),
+    AttrSpec::new(7, "IFA_MULTICAST", AttrKind::Addr),
+    AttrSpec::new(8, "IFA_FLAGS", AttrKind::U32),
+    AttrSpec::new(9, "IFA_RT_PRIORITY", AttrKind::U32),
+    AttrSpec::new(10, "IFA_TARGET_NETNSID", AttrKind::I32),
+    AttrSpec::new(11, "IFA_PROTO", AttrKind::U8),
+];
+
+impl MessageDecoder for AddrDecoder {
+    fn type_name(&self, typ: u16) -> Option<&'static str> {
+        match deserialize_repr::<RouteMessageType>(typ)? {
+            RouteMessageType::NewAddr => Some("RTM_NEWADDR"),
+            RouteMessageType::DelAddr => Some("RTM_DELADDR"),
+            RouteMessageType::GetAddr => Some("RTM_GETADDR"),
+            _ => None,
+        }
+    }
+
+    fn flag_kind(&self, typ: u16) -> FlagKind {
+        match deserialize_repr::<RouteMessageType>(typ) {
+            Some(RouteMessageType::NewAddr) => FlagKind::New,
+            Some(RouteMessageType::DelAddr) => FlagKind::Delete,
+            Some(RouteMessageType::GetAddr) => FlagKind::Get,
+            _ => FlagKind::None,
+        }
+    }
+
+    fn decode(&self, _typ: u16, payload: &[u8]) -> Result<Vec<FieldValue>> {
+        let mut reader = SliceReader::new(payload);
+        let ifaddrmsg = reader.read::<InterfaceAddrMessage>()?;
+
+        let header = vec![
+            Field::text("ifa_family", &family_name(ifaddrmsg.family)),
+            Field::text("ifa_prefixlen", &ifaddrmsg.prefixlen),
+            Field::text("ifa_flags", &format!("{:#x}", ifaddrmsg.flags)),
+            Field::text("ifa_scope", &ifaddrmsg.scope),
+            Field::text("ifa_index", &ifaddrmsg.index),
+        ];
+
+        let attrs = decode_attributes(reader.take(reader.remaining())?, IFA_ATTRS)?;
+        Ok(vec![
+            FieldValue::Struct(header),
+            FieldValue::Attributes(attrs),
+        ])
+    }
+}
@@ -0,0 +1,305 @@
+use crate::bytes::{serialize_aligned, serialize_ascii, serialize_attribute};
+use crate::route::link::{InterfaceInfoMessage, LinkAttrType};
+use crate::route::route::RouteMessageType;
+use crate::route::AF_UNSPEC;
+use crate::transport::Transport;
+use crate::Result;
+use crate::{Error, Flags, GetFlags, MessageFlags, NetlinkMessage, NetlinkRequest, NetlinkStream};
+use nix::errno::Errno;
+
+/// Optional features of `NETLINK_ROUTE` that the running kernel supports. See
+/// [`NetlinkStream::capabilities`].
+///
+/// Features are detected by trying them, so this reflects what the kernel
+/// actually accepts rather than its version.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Capabilities {
+    /// The `NETLINK_GET_STRICT_CHK` socket option is available, so GET
+    /// requests can be validated and filtered by the kernel (Linux 4.20)
+    pub strict_checking: bool,
+    /// Nexthop objects can be dumped with [`RouteMessageType::GetNexthop`]
+    /// (Linux 5.3)
+    pub nexthop_objects: bool,
+    /// Interfaces can be looked up by an alternative name with
+    /// `IFLA_ALT_IFNAME`. Alternative names are reported in `IFLA_PROP_LIST`,
+    /// which was added at the same time (Linux 5.5).
+    pub alt_ifnames: bool,
+}
+
+impl<T: Transport> NetlinkStream<T> {
+    /// Detect which optional features the kernel supports. The kernel is only
+    /// probed the first time, after which the result is cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] if a probe fails for any other reason than
+    /// the feature being unsupported.
+    pub fn capabilities(&mut self) -> Result<Capabilities> {
+        if let Some(capabilities) = self.capabilities {
+            return Ok(capabilities);
+        }
+
+        let capabilities = Capabilities {
+            strict_checking: self.probe_strict_checking()?,
+            nexthop_objects: self.probe(&Probe::nexthop_dump())?,
+            alt_ifnames: self.probe(&Probe::alt_ifname_lookup()?)?,
+        };
+
+        self.capabilities = Some(capabilities);
+        Ok(capabilities)
+    }
+
+    /// Run `f` with strict checking turned on if the kernel supports it, so
+    /// that the kernel applies the filters of dump requests. The previous
+    /// setting of the socket is restored afterwards, so that other requests
+    /// aren't validated differently.
+    ///
+    /// Only the socket option is tried, rather than every capability, so that
+    /// nothing but the caller's requests is sent. When it can't be turned on,
+    /// `f` runs without it, and the client filters the responses instead.
+    pub(crate) fn with_strict_checking<U>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<U>,
+    ) -> Result<U> {
+        if self.capabilities.is_some_and(|caps| !caps.strict_checking) {
+            return f(self);
+        }
+
+        let Ok(previous) = self.get_ref().strict_checking() else {
+            return f(self);
+        };
+        if !previous && self.get_mut().set_strict_checking(true).is_err() {
+            return f(self);
+        }
+
+        let res = f(self);
+
+        if !previous {
+            // The error of the request matters more than this one
+            let restored = self.get_mut().set_strict_checking(false);
+            return res.and_then(|value| restored.map(|()| value));
+        }
+        res
+    }
+
+    fn probe_strict_checking(&mut self) -> Result<bool> {
+        match self.get_ref().strict_checking() {
+            Ok(_) => Ok(true),
+            Err(Error::ErrSocketOption(Errno::ENOPROTOOPT)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn probe(&mut self, probe: &Probe) -> Result<bool> {
+        match self.execute(probe) {
+            Ok(_) => Ok(true),
+            Err(Error::ErrKernel(Errno::EOPNOTSUPP | Errno::EINVAL)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// A trial request whose response is ignored. Kernels that don't support the
+/// feature reject it with `EOPNOTSUPP` or `EINVAL`.
+struct Probe {
+    typ: RouteMessageType,
+    dump: bool,
+    payload: Vec<u8>,
+}
+
+impl Probe {
+    // Kernels without nexthop objects have no handler for the message type
+    fn nexthop_dump() -> Self {
+        // struct nhmsg is 4 single-byte fields followed by a u32 of flags
+        let nhmsg = [AF_UNSPEC, 0, 0, 0, 0, 0, 0, 0];
+
+        Self {
+            typ: RouteMessageType::GetNexthop,
+            dump: true,
+            payload: nhmsg.to_vec(),
+        }
+    }
+
+    // Kernels without alternative names ignore the attribute, and reject a
+    // lookup without an index or a name. Every namespace has a loopback
+    // interface, and its primary name matches as well.
+    fn alt_ifname_lookup() -> Result<Self> {
+        let mut ifinfomsg = InterfaceInfoMessage::builder().build()?;
+        ifinfomsg.change = 0;
+
+        let mut payload = serialize_aligned(ifinfomsg)?;
+        let name = serialize_ascii("lo");
+        payload.append(&mut serialize_attribute(
+            LinkAttrType::AltInterfaceName.into(),
+            &name,
+        )?);
+
+        Ok(Self {
+            typ: RouteMessageType::GetLink,
+            dump: false,
+            payload,
+        })
+    }
+}
+
+impl NetlinkRequest for Probe {
+    type Flags = MessageFlags<GetFlags>;
+    type Response = ();
+
+    fn message_type(&self) -> u16 {
+        self.typ.into()
+    }
+
+    fn flags(&self) -> Self::Flags {
+        let modifiers = if self.dump {
+            GetFlags::DUMP
+        } else {
+            GetFlags::empty()
+        };
+        Flags::REQUEST | modifiers
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        Ok(self.payload.clone())
+    }
+
+    fn deserialize(&self, _msg: &NetlinkMessage) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::SliceReader;
+    use crate::codec::serialize_message;
+    use crate::route::link::LinkFilter;
+    use crate::route::route::RouteFilter;
+    use crate::route::AddressFamily;
+    use crate::testing::FakeKernel;
+    use crate::{Flags, MessageType, NetlinkHeader};
+
+    // Answers every dump with an empty response, and records whether strict
+    // checking was on when each request was sent
+    #[derive(Default)]
+    struct StrictTransport {
+        strict: bool,
+        sent_strict: Vec<bool>,
+        seq: Option<u32>,
+    }
+
+    impl Transport for StrictTransport {
+        fn pid(&self) -> u32 {
+            0
+        }
+
+        fn send(&mut self, buf: &[u8]) -> Result<()> {
+            let hdr = SliceReader::new(buf).read::<NetlinkHeader>()?;
+            self.seq = Some(hdr.seq);
+            self.sent_strict.push(self.strict);
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>> {
+            let seq = self.seq.take().ok_or(Error::ErrRecvSocket(Errno::EAGAIN))?;
+            let status = 0i32.to_le_bytes().to_vec();
+            let flags = Flags::MULTI.bits();
+            serialize_message(MessageType::Done.into(), flags, seq, 0, status)
+        }
+
+        fn strict_checking(&self) -> Result<bool> {
+            Ok(self.strict)
+        }
+
+        fn set_strict_checking(&mut self, enable: bool) -> Result<()> {
+            self.strict = enable;
+            Ok(())
+        }
+    }
+
+    fn stream() -> NetlinkStream<StrictTransport> {
+        let mut conn = NetlinkStream::new(StrictTransport::default());
+        conn.capabilities = Some(Capabilities {
+            strict_checking: true,
+            ..Default::default()
+        });
+        conn
+    }
+
+    #[test]
+    fn filtered_dump_restores_strict_checking() -> Result<()> {
+        let mut conn = stream();
+
+        let filter = RouteFilter::default();
+        conn.list_routes_filtered(AddressFamily::Inet, &filter)?;
+        conn.list_routes()?;
+
+        assert_eq!(conn.get_ref().sent_strict, [true, false]);
+        assert!(!conn.get_ref().strict_checking()?);
+        Ok(())
+    }
+
+    #[test]
+    fn filtered_dump_keeps_strict_checking() -> Result<()> {
+        let mut conn = stream();
+        conn.get_mut().set_strict_checking(true)?;
+
+        conn.list_links_filtered(&LinkFilter::default())?;
+
+        assert_eq!(conn.get_ref().sent_strict, [true]);
+        assert!(conn.get_ref().strict_checking()?);
+        Ok(())
+    }
+
+    #[test]
+    fn filtered_dump_sends_no_probes() -> Result<()> {
+        let mut conn = NetlinkStream::new(FakeKernel::new());
+        conn.list_routes_filtered(AddressFamily::Inet, &RouteFilter::default())?;
+
+        let types: Vec<_> = conn
+            .get_ref()
+            .requests()
+            .iter()
+            .map(|req| req.header.typ())
+            .collect();
+        assert_eq!(types, [RouteMessageType::GetRoute.into()]);
+        assert!(conn.capabilities.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_features_are_detected() -> Result<()> {
+        let kernel = FakeKernel::new().with_error(RouteMessageType::GetLink, Errno::EINVAL);
+        let mut conn = NetlinkStream::new(kernel);
+
+        // FakeKernel has no strict checking, and no handler for nexthops
+        assert_eq!(conn.capabilities()?, Capabilities::default());
+        Ok(())
+    }
+
+    #[test]
+    fn supported_features_are_detected() -> Result<()> {
+        let kernel = FakeKernel::new()
+            .with_error_code(RouteMessageType::GetNexthop, 0)
+            .with_error_code(RouteMessageType::GetLink, 0);
+        let mut conn = NetlinkStream::new(kernel);
+
+        let capabilities = conn.capabilities()?;
+        assert!(capabilities.nexthop_objects);
+        assert!(capabilities.alt_ifnames);
+
+        // The result is cached
+        conn.capabilities()?;
+        assert_eq!(conn.get_ref().requests().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn failed_probe_is_reported() {
+        let kernel = FakeKernel::new().with_error(RouteMessageType::GetNexthop, Errno::EPERM);
+        let mut conn = NetlinkStream::new(kernel);
+
+        let err = conn.capabilities().unwrap_err();
+        assert_eq!(err.errno(), Some(Errno::EPERM));
+    }
+}
@@ -1,2 +1,21 @@
+/// Unspecified address family
+pub const AF_UNSPEC: u8 = 0;
+
 /// Ipv4 address family
 pub const AF_INET: u8 = 2;
+
+/// Ipv6 address family
+pub const AF_INET6: u8 = 10;
+
+/// Notify the requester of the result of a route lookup
+pub const RTM_F_NOTIFY: u32 = 0x100;
+
+/// Cached routes, like PMTU exceptions, as opposed to configured ones
+pub const RTM_F_CLONED: u32 = 0x200;
+
+/// Route lookups return the table the route was found in
+pub const RTM_F_LOOKUP_TABLE: u32 = 0x1000;
+
+/// Route lookups return the matching route as it's installed, instead of the
+/// route resolved for the destination
+pub const RTM_F_FIB_MATCH: u32 = 0x2000;
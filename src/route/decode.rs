@@ -0,0 +1,153 @@
+use crate::bytes::deserialize_repr;
+use crate::route::addr::{parse_addr, Address, InterfaceAddrMessage};
+use crate::route::link::{parse_link, InterfaceInfoMessage, Link};
+use crate::route::route::{parse_route, Route, RouteMessage, RouteMessageType};
+use crate::route::rule::{parse_rule, Rule, RuleMessage};
+use crate::{NetlinkMessage, Result};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::mem::size_of;
+
+/// A `NETLINK_ROUTE` message decoded into the types used by the rest of this
+/// crate. Requests and responses are decoded the same way, so the objects in
+/// requests may only have a few fields set.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub enum RtnlMessage {
+    NewLink(Link),
+    DelLink(Link),
+    GetLink(Link),
+    SetLink(Link),
+    NewAddr(Address),
+    DelAddr(Address),
+    GetAddr(Address),
+    NewRoute(Route),
+    DelRoute(Route),
+    GetRoute(Route),
+    NewRule(Rule),
+    DelRule(Rule),
+    GetRule(Rule),
+}
+
+/// Decode a message sent to or received from a `NETLINK_ROUTE` socket, such
+/// as one read from a capture with [`crate::PcapReader`].
+///
+/// Returns [`None`] for messages that don't carry an rtnetlink object, like
+/// [`crate::MessageType::Done`], or have a type this crate doesn't support.
+///
+/// Dump requests that only carry the family in a one-byte `rtgenmsg`, like
+/// the ones `getifaddrs(3)` sends, are decoded as if the rest of the header
+/// was zero.
+///
+/// # Errors
+///
+/// Returns an [`crate::Error`] if the payload is malformed.
+pub fn decode_message(msg: &NetlinkMessage) -> Result<Option<RtnlMessage>> {
+    let Some(typ) = deserialize_repr::<RouteMessageType>(msg.header.typ) else {
+        return Ok(None);
+    };
+
+    let payload = &*request_payload(typ, &msg.payload);
+    let decoded = match typ {
+        RouteMessageType::NewLink => RtnlMessage::NewLink(parse_link(payload)?),
+        RouteMessageType::DelLink => RtnlMessage::DelLink(parse_link(payload)?),
+        RouteMessageType::GetLink => RtnlMessage::GetLink(parse_link(payload)?),
+        RouteMessageType::SetLink => RtnlMessage::SetLink(parse_link(payload)?),
+        RouteMessageType::NewAddr => RtnlMessage::NewAddr(parse_addr(payload)?),
+        RouteMessageType::DelAddr => RtnlMessage::DelAddr(parse_addr(payload)?),
+        RouteMessageType::GetAddr => RtnlMessage::GetAddr(parse_addr(payload)?),
+        RouteMessageType::NewRoute => RtnlMessage::NewRoute(parse_route(payload)?),
+        RouteMessageType::DelRoute => RtnlMessage::DelRoute(parse_route(payload)?),
+        RouteMessageType::GetRoute => RtnlMessage::GetRoute(parse_route(payload)?),
+        RouteMessageType::NewRule => RtnlMessage::NewRule(parse_rule(payload)?),
+        RouteMessageType::DelRule => RtnlMessage::DelRule(parse_rule(payload)?),
+        RouteMessageType::GetRule => RtnlMessage::GetRule(parse_rule(payload)?),
+        RouteMessageType::NewNexthop
+        | RouteMessageType::DelNexthop
+        | RouteMessageType::GetNexthop => return Ok(None),
+    };
+
+    Ok(Some(decoded))
+}
+
+// Extend the payload of a GET request that is shorter than the header of its
+// type with zeros, since older requests only start with the family
+fn request_payload(typ: RouteMessageType, payload: &[u8]) -> Cow<'_, [u8]> {
+    let header_len = match typ {
+        RouteMessageType::GetLink => size_of::<InterfaceInfoMessage>(),
+        RouteMessageType::GetAddr => size_of::<InterfaceAddrMessage>(),
+        RouteMessageType::GetRoute => size_of::<RouteMessage>(),
+        RouteMessageType::GetRule => size_of::<RuleMessage>(),
+        _ => return Cow::Borrowed(payload),
+    };
+    if payload.len() >= header_len {
+        return Cow::Borrowed(payload);
+    }
+
+    let mut padded = payload.to_vec();
+    padded.resize(header_len, 0);
+    Cow::Owned(padded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::split_messages;
+    use crate::route::AddressFamily;
+
+    fn decode_datagram(datagram: &[u8]) -> Result<Vec<Option<RtnlMessage>>> {
+        split_messages(datagram)?
+            .into_iter()
+            .map(|(hdr, payload)| {
+                decode_message(&NetlinkMessage::new(hdr.into_descriptor(), payload))
+            })
+            .collect()
+    }
+
+    // The RTM_GETLINK and RTM_GETADDR dumps glibc's getifaddrs() sends, with
+    // an AF_UNSPEC rtgenmsg padded to 4 bytes
+    #[rustfmt::skip]
+    const RTGENMSG_DUMPS: &[u8] = &[
+        0x14, 0x00, 0x00, 0x00, 0x12, 0x00, 0x01, 0x03,
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x00, 0x00, 0x16, 0x00, 0x01, 0x03,
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    // An IPv4 route dump request whose rtgenmsg isn't padded, so its length
+    // is 17
+    #[rustfmt::skip]
+    const SHORT_ROUTE_DUMP: &[u8] = &[
+        0x11, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x01, 0x03,
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x02, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn rtgenmsg_requests_are_decoded() -> Result<()> {
+        let decoded = decode_datagram(RTGENMSG_DUMPS)?;
+        assert_eq!(
+            decoded,
+            [
+                Some(RtnlMessage::GetLink(Link::default())),
+                Some(RtnlMessage::GetAddr(Address::default())),
+            ]
+        );
+
+        let decoded = decode_datagram(SHORT_ROUTE_DUMP)?;
+        let Some(Some(RtnlMessage::GetRoute(route))) = decoded.first() else {
+            panic!("not a route request: {decoded:?}");
+        };
+        assert_eq!(route.family, AddressFamily::Inet);
+        Ok(())
+    }
+
+    #[test]
+    fn short_responses_are_rejected() {
+        let mut datagram = SHORT_ROUTE_DUMP.to_vec();
+        // RTM_NEWROUTE
+        datagram[4] = 24;
+        assert!(decode_datagram(&datagram).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::route::route::RouteMessageType;
+use crate::Error;
+use nix::errno::Errno;
+use std::fmt;
+use std::net::IpAddr;
+
+/// What went wrong with an rtnetlink operation, derived from the errno the
+/// kernel reported and the kind of request. See [`OperationError`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum OperationErrorKind {
+    /// A route with the same key already exists (`EEXIST` on
+    /// [`RouteMessageType::NewRoute`])
+    RouteExists,
+    /// The route to delete or get doesn't exist (`ESRCH`)
+    NoSuchRoute,
+    /// The address already exists (`EEXIST` on [`RouteMessageType::NewAddr`])
+    AddressExists,
+    /// The link already exists (`EEXIST` on [`RouteMessageType::NewLink`])
+    LinkExists,
+    /// The object already exists (`EEXIST`)
+    Exists,
+    /// The object doesn't exist (`ENOENT`)
+    NotFound,
+    /// The interface doesn't exist (`ENODEV`)
+    NoSuchDevice,
+    /// The operation requires the `CAP_NET_ADMIN` capability (`EPERM`,
+    /// `EACCES`)
+    PermissionDenied,
+    /// The kernel doesn't support the operation or the address family
+    /// (`EOPNOTSUPP`, `EAFNOSUPPORT`, `EPROTONOSUPPORT`)
+    NotSupported,
+    /// The address is already in use (`EADDRINUSE`)
+    AddressInUse,
+    /// The address isn't assigned to an interface (`EADDRNOTAVAIL`)
+    AddressNotAvailable,
+    /// The gateway isn't reachable from the interface (`ENETUNREACH`)
+    NetworkUnreachable,
+    /// The request is malformed or inconsistent (`EINVAL`)
+    InvalidArgument,
+    /// The kernel is busy, and the request should be retried (`EBUSY`,
+    /// `EAGAIN`)
+    Busy,
+    /// Any other errno
+    Other,
+}
+
+impl OperationErrorKind {
+    /// Classify an errno reported in response to a request of type `typ`.
+    #[must_use]
+    pub fn classify(typ: RouteMessageType, errno: Errno) -> Self {
+        match (typ, errno) {
+            (RouteMessageType::NewRoute, Errno::EEXIST) => Self::RouteExists,
+            (RouteMessageType::NewAddr, Errno::EEXIST) => Self::AddressExists,
+            (RouteMessageType::NewLink, Errno::EEXIST) => Self::LinkExists,
+            (_, Errno::EEXIST) => Self::Exists,
+            (RouteMessageType::DelRoute | RouteMessageType::GetRoute, Errno::ESRCH) => {
+                Self::NoSuchRoute
+            }
+            (_, Errno::ENOENT | Errno::ESRCH) => Self::NotFound,
+            (_, Errno::ENODEV) => Self::NoSuchDevice,
+            (_, Errno::EPERM | Errno::EACCES) => Self::PermissionDenied,
+            (_, Errno::EOPNOTSUPP | Errno::EAFNOSUPPORT | Errno::EPROTONOSUPPORT) => {
+                Self::NotSupported
+            }
+            (_, Errno::EADDRINUSE) => Self::AddressInUse,
+            (_, Errno::EADDRNOTAVAIL) => Self::AddressNotAvailable,
+            (_, Errno::ENETUNREACH) => Self::NetworkUnreachable,
+            (_, Errno::EINVAL) => Self::InvalidArgument,
+            (_, Errno::EBUSY | Errno::EAGAIN) => Self::Busy,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether the object the request tried to create already exists.
+    #[must_use]
+    pub fn is_exists(self) -> bool {
+        matches!(
+            self,
+            Self::RouteExists | Self::AddressExists | Self::LinkExists | Self::Exists
+        )
+    }
+
+    /// Whether the object the request refers to doesn't exist.
+    #[must_use]
+    pub fn is_not_found(self) -> bool {
+        matches!(
+            self,
+            Self::NoSuchRoute | Self::NotFound | Self::NoSuchDevice
+        )
+    }
+}
+
+impl fmt::Display for OperationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::RouteExists => "route already exists",
+            Self::NoSuchRoute => "no such route",
+            Self::AddressExists => "address already exists",
+            Self::LinkExists => "link already exists",
+            Self::Exists => "object already exists",
+            Self::NotFound => "object not found",
+            Self::NoSuchDevice => "no such device",
+            Self::PermissionDenied => "permission denied, CAP_NET_ADMIN is required",
+            Self::NotSupported => "operation not supported",
+            Self::AddressInUse => "address already in use",
+            Self::AddressNotAvailable => "address not available",
+            Self::NetworkUnreachable => "network unreachable",
+            Self::InvalidArgument => "invalid argument",
+            Self::Busy => "resource busy",
+            Self::Other => "operation failed",
+        };
+        f.write_str(text)
+    }
+}
+
+/// The request an [`OperationError`] was reported for.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RequestContext {
+    /// Type of the request
+    pub message_type: RouteMessageType,
+    /// Index of the interface the request refers to
+    pub interface: Option<i32>,
+    /// Address and prefix length the request refers to
+    pub prefix: Option<(IpAddr, u8)>,
+}
+
+impl RequestContext {
+    #[must_use]
+    pub fn new(message_type: RouteMessageType) -> Self {
+        Self {
+            message_type,
+            interface: None,
+            prefix: None,
+        }
+    }
+
+    /// Attach the index of the interface the request refers to.
+    #[must_use]
+    pub fn interface(mut self, index: i32) -> Self {
+        self.interface = Some(index);
+        self
+    }
+
+    /// Attach the address and prefix length the request refers to.
+    #[must_use]
+    pub fn prefix(mut self, addr: IpAddr, len: u8) -> Self {
+        self.prefix = Some((addr, len));
+        self
+    }
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.message_type)?;
+        if let Some((addr, len)) = self.prefix {
+            write!(f, " {addr}/{len}")?;
+        }
+        if let Some(index) = self.interface {
+            write!(f, " on interface {index}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error reported by the kernel in response to an rtnetlink operation.
+///
+/// Rather than matching on the raw errno, callers can branch on the
+/// [`OperationErrorKind`]:
+///
+/// ```rust
+/// use netlink::route::route::RouteMessageType;
+/// use netlink::route::{OperationError, OperationErrorKind, RequestContext};
+/// use nix::errno::Errno;
+///
+/// let context = RequestContext::new(RouteMessageType::NewRoute);
+/// let err = netlink::Error::from(OperationError::new(context, Errno::EEXIST));
+///
+/// assert_eq!(err.operation_kind(), Some(OperationErrorKind::RouteExists));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OperationError {
+    pub kind: OperationErrorKind,
+    pub errno: Errno,
+    pub context: RequestContext,
+}
+
+impl OperationError {
+    /// Classify the errno reported for a request.
+    #[must_use]
+    pub fn new(context: RequestContext, errno: Errno) -> Self {
+        Self {
+            kind: OperationErrorKind::classify(context.message_type, errno),
+            errno,
+            context,
+        }
+    }
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}) for {}", self.kind, self.errno, self.context)
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+impl From<OperationError> for Error {
+    fn from(err: OperationError) -> Self {
+        Self::ErrOperation {
+            errno: err.errno,
+            error: Box::new(err),
+        }
+    }
+}
+
+// The classification is rtnetlink's, so it's looked up here rather than in
+// the core error type
+impl Error {
+    /// The rtnetlink operation that failed, if this error was reported by the
+    /// kernel in response to one.
+    #[must_use]
+    pub fn operation_error(&self) -> Option<&OperationError> {
+        match self {
+            Self::ErrOperation { error, .. } => error.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// What went wrong with an rtnetlink operation, if this error was reported
+    /// by the kernel in response to one.
+    #[must_use]
+    pub fn operation_kind(&self) -> Option<OperationErrorKind> {
+        self.operation_error().map(|err| err.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn exists_depends_on_the_request() {
+        let cases = [
+            (RouteMessageType::NewRoute, OperationErrorKind::RouteExists),
+            (RouteMessageType::NewAddr, OperationErrorKind::AddressExists),
+            (RouteMessageType::NewLink, OperationErrorKind::LinkExists),
+            (RouteMessageType::NewRule, OperationErrorKind::Exists),
+        ];
+        for (typ, kind) in cases {
+            assert_eq!(OperationErrorKind::classify(typ, Errno::EEXIST), kind);
+            assert!(kind.is_exists());
+            assert!(!kind.is_not_found());
+        }
+    }
+
+    #[test]
+    fn missing_route_is_reported_as_such() {
+        for typ in [RouteMessageType::DelRoute, RouteMessageType::GetRoute] {
+            assert_eq!(
+                OperationErrorKind::classify(typ, Errno::ESRCH),
+                OperationErrorKind::NoSuchRoute
+            );
+        }
+        for errno in [Errno::ESRCH, Errno::ENOENT] {
+            assert_eq!(
+                OperationErrorKind::classify(RouteMessageType::DelRule, errno),
+                OperationErrorKind::NotFound
+            );
+        }
+        assert_eq!(
+            OperationErrorKind::classify(RouteMessageType::DelRoute, Errno::ENOENT),
+            OperationErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn errnos_are_classified_for_any_request() {
+        let cases = [
+            (Errno::ENODEV, OperationErrorKind::NoSuchDevice),
+            (Errno::EPERM, OperationErrorKind::PermissionDenied),
+            (Errno::EACCES, OperationErrorKind::PermissionDenied),
+            (Errno::EOPNOTSUPP, OperationErrorKind::NotSupported),
+            (Errno::EAFNOSUPPORT, OperationErrorKind::NotSupported),
+            (Errno::EPROTONOSUPPORT, OperationErrorKind::NotSupported),
+            (Errno::EADDRINUSE, OperationErrorKind::AddressInUse),
+            (
+                Errno::EADDRNOTAVAIL,
+                OperationErrorKind::AddressNotAvailable,
+            ),
+            (Errno::ENETUNREACH, OperationErrorKind::NetworkUnreachable),
+            (Errno::EINVAL, OperationErrorKind::InvalidArgument),
+            (Errno::EBUSY, OperationErrorKind::Busy),
+            (Errno::EAGAIN, OperationErrorKind::Busy),
+            (Errno::ENOMEM, OperationErrorKind::Other),
+            (Errno::UnknownErrno, OperationErrorKind::Other),
+        ];
+        let types = [
+            RouteMessageType::NewRoute,
+            RouteMessageType::DelAddr,
+            RouteMessageType::GetLink,
+            RouteMessageType::NewRule,
+        ];
+        for (errno, kind) in cases {
+            for typ in types {
+                assert_eq!(
+                    OperationErrorKind::classify(typ, errno),
+                    kind,
+                    "{typ:?} {errno}"
+                );
+            }
+        }
+        assert!(OperationErrorKind::NoSuchDevice.is_not_found());
+    }
+
+    #[test]
+    fn operation_error_is_wrapped() {
+        let context = RequestContext::new(RouteMessageType::NewAddr)
+            .interface(2)
+            .prefix(Ipv4Addr::new(192, 0, 2, 1).into(), 24);
+        let err = Error::from(OperationError::new(context.clone(), Errno::EEXIST));
+
+        assert_eq!(err.errno(), Some(Errno::EEXIST));
+        assert_eq!(
+            err.operation_kind(),
+            Some(OperationErrorKind::AddressExists)
+        );
+        assert_eq!(
+            err.operation_error().map(|err| &err.context),
+            Some(&context)
+        );
+        assert_eq!(
+            err.to_string(),
+            "address already exists (EEXIST: File exists) for NewAddr 192.0.2.1/24 on interface 2"
+        );
+
+        let err = Error::ErrKernel(Errno::EEXIST);
+        assert_eq!(err.errno(), Some(Errno::EEXIST));
+        assert_eq!(err.operation_kind(), None);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::bytes::deserialize_repr;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::net::IpAddr;
+
+/// Address family of an rtnetlink request or object, like the `rtm_family` of
+/// a route. Dumps only return objects of the family they were requested for,
+/// or of every family with [`AddressFamily::Unspec`].
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Serialize_repr, Deserialize_repr)]
+pub enum AddressFamily {
+    /// Every family (`AF_UNSPEC`)
+    #[default]
+    Unspec = 0,
+    /// IPv4 (`AF_INET`)
+    Inet = 2,
+    /// Bridge ports and forwarding entries (`AF_BRIDGE`)
+    Bridge = 7,
+    /// IPv6 (`AF_INET6`)
+    Inet6 = 10,
+    /// MPLS label routes (`AF_MPLS`)
+    Mpls = 28,
+    /// IPv4 multicast routes (`RTNL_FAMILY_IPMR`)
+    Ipmr = 128,
+    /// IPv6 multicast routes (`RTNL_FAMILY_IP6MR`)
+    Ip6mr = 129,
+}
+
+impl AddressFamily {
+    /// The family of an IP address.
+    #[must_use]
+    pub fn of(addr: &IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => Self::Inet,
+            IpAddr::V6(_) => Self::Inet6,
+        }
+    }
+}
+
+impl From<AddressFamily> for u8 {
+    fn from(family: AddressFamily) -> Self {
+        family as u8
+    }
+}
+
+impl TryFrom<u8> for AddressFamily {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        deserialize_repr(value.into()).ok_or(value)
+    }
+}
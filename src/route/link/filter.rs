@@ -0,0 +1,69 @@
+use super::{Link, LinkAttrValue};
+use crate::bytes::{serialize_ascii, serialize_attribute, serialize_nested_attribute};
+use crate::Error;
+use crate::Result;
+use derive_builder::Builder;
+
+/// Type of the attribute nested in `IFLA_LINKINFO` that holds the kind
+pub(crate) const IFLA_INFO_KIND: u16 = 1;
+
+/// Selects the interfaces returned by [`crate::NetlinkStream::list_links_filtered`].
+///
+/// The kernel applies the filter itself and only sends the matching
+/// interfaces. Kernels that can't are still filtered by the client, so the
+/// result is the same everywhere.
+///
+/// ```rust
+/// use netlink::route::link::LinkFilter;
+///
+/// # fn main() -> netlink::Result<()> {
+/// let filter = LinkFilter::builder().kind("bridge").build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct LinkFilter {
+    /// Only interfaces enslaved to the interface with this index, like the
+    /// ports of a bridge
+    pub master: Option<u32>,
+    /// Only interfaces of this kind, like `bridge` or `veth`
+    pub kind: Option<String>,
+}
+
+impl LinkFilter {
+    #[must_use]
+    pub fn builder() -> LinkFilterBuilder {
+        LinkFilterBuilder::default()
+    }
+
+    /// Whether the interface is selected by this filter.
+    #[must_use]
+    pub fn matches(&self, link: &Link) -> bool {
+        if self.master.is_some() && link.master != self.master {
+            return false;
+        }
+        if self.kind.is_some() && link.kind != self.kind {
+            return false;
+        }
+        true
+    }
+
+    // The attributes that ask the kernel to apply the filter.
+    pub(crate) fn serialize(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+
+        if let Some(master) = self.master {
+            let (typ, value) = LinkAttrValue::Master(master).serialize();
+            bytes.append(&mut serialize_attribute(typ.into(), &value)?);
+        }
+
+        if let Some(kind) = &self.kind {
+            let info = serialize_attribute(IFLA_INFO_KIND, &serialize_ascii(kind))?;
+            let (typ, _) = LinkAttrValue::Linkinfo(vec![]).serialize();
+            bytes.append(&mut serialize_nested_attribute(typ.into(), &info)?);
+        }
+
+        Ok(bytes)
+    }
+}
@@ -1,5 +1,11 @@
 mod client;
 pub use client::*;
 
+mod filter;
+pub use filter::*;
+
 pub mod types;
 pub use types::*;
+
+mod pretty;
+pub use pretty::*;
@@ -0,0 +1,149 @@
+use super::InterfaceInfoMessage;
+use crate::bytes::{deserialize_repr, SliceReader};
+use crate::pretty::{decode_attributes, family_name, AttrKind, AttrSpec, Field, FieldValue};
+use crate::pretty::{FlagKind, MessageDecoder};
+use crate::route::route::RouteMessageType;
+use crate::Result;
+
+/// Decodes `RTM_*LINK` messages. See [`crate::DecoderRegistry`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LinkDecoder;
+
+#[rustfmt::skip]
+const IFLA_INFO_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "IFLA_INFO_KIND", AttrKind::Str),
+    AttrSpec::new(2, "IFLA_INFO_DATA", AttrKind::Bytes),
+    AttrSpec::new(3, "IFLA_INFO_XSTATS", AttrKind::Bytes),
+    AttrSpec::new(4, "IFLA_INFO_SLAVE_KIND", AttrKind::Str),
+    AttrSpec::new(5, "IFLA_INFO_SLAVE_DATA", AttrKind::Bytes),
+];
+
+#[rustfmt::skip]
+const IFLA_XDP_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "IFLA_XDP_FD", AttrKind::I32),
+    AttrSpec::new(2, "IFLA_XDP_ATTACHED", AttrKind::U8),
+    AttrSpec::new(3, "IFLA_XDP_FLAGS", AttrKind::U32),
+    AttrSpec::new(4, "IFLA_XDP_PROG_ID", AttrKind::U32),
+    AttrSpec::new(5, "IFLA_XDP_DRV_PROG_ID", AttrKind::U32),
+    AttrSpec::new(6, "IFLA_XDP_SKB_PROG_ID", AttrKind::U32),
+    AttrSpec::new(7, "IFLA_XDP_HW_PROG_ID", AttrKind::U32),
+    AttrSpec::new(8, "IFLA_XDP_EXPECTED_FD", AttrKind::I32),
+];
+
+#[rustfmt::skip]
+const IFLA_PROP_LIST_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(53, "IFLA_ALT_IFNAME", AttrKind::Str),
+];
+
+#[rustfmt::skip]
+const IFLA_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "IFLA_ADDRESS", AttrKind::HwAddr),
+    AttrSpec::new(2, "IFLA_BROADCAST", AttrKind::HwAddr),
+    AttrSpec::new(3, "IFLA_IFNAME", AttrKind::Str),
+    AttrSpec::new(4, "IFLA_MTU", AttrKind::U32),
+    AttrSpec::new(5, "IFLA_LINK", AttrKind::U32),
+    AttrSpec::new(6, "IFLA_QDISC", AttrKind::Str),
+    AttrSpec::new(7, "IFLA_STATS", AttrKind::Bytes),
+    AttrSpec::new(8, "IFLA_COST", AttrKind::Bytes),
+    AttrSpec::new(9, "IFLA_PRIORITY", AttrKind::Bytes),
+    AttrSpec::new(10, "IFLA_MASTER", AttrKind::U32),
+    AttrSpec::new(11, "IFLA_WIRELESS", AttrKind::Bytes),
+    AttrSpec::new(12, "IFLA_PROTINFO", AttrKind::Bytes),
+    AttrSpec::new(13, "IFLA_TXQLEN", AttrKind::U32),
+    AttrSpec::new(14, "IFLA_MAP", AttrKind::Bytes),
+    AttrSpec::new(15, "IFLA_WEIGHT", AttrKind::U32),
+    AttrSpec::new(16, "IFLA_OPERSTATE", AttrKind::U8),
+    AttrSpec::new(17, "IFLA_LINKMODE", AttrKind::U8),
+    AttrSpec::new(18, "IFLA_LINKINFO", AttrKind::Nested(IFLA_INFO_ATTRS)),
+    AttrSpec::new(19, "IFLA_NET_NS_PID", AttrKind::U32),
+    AttrSpec::new(20, "IFLA_IFALIAS", AttrKind::Str),
+    AttrSpec::new(21, "IFLA_NUM_VF", AttrKind::U32),
+    AttrSpec::new(22, "IFLA_VFINFO_LIST", AttrKind::Bytes),
+    AttrSpec::new(23, "IFLA_STATS64", AttrKind::Bytes),
+    AttrSpec::new(24, "IFLA_VF_PORTS", AttrKind::Bytes),
+    AttrSpec::new(25, "IFLA_PORT_SELF", AttrKind::Bytes),
+    AttrSpec::new(26, "IFLA_AF_SPEC", AttrKind::Bytes),
+    AttrSpec::new(27, "IFLA_GROUP", AttrKind::U32),
+    AttrSpec::new(28, "IFLA_NET_NS_FD", AttrKind::U32),
+    AttrSpec::new(29, "IFLA_EXT_MASK", AttrKind::U32),
+    AttrSpec::new(30, "IFLA_PROMISCUITY", AttrKind::U32),
+    AttrSpec::new(31, "IFLA_NUM_TX_QUEUES", AttrKind::U32),
+    AttrSpec::new(32, "IFLA_NUM_RX_QUEUES", AttrKind::U32),
+    AttrSpec::new(33, "IFLA_CARRIER", AttrKind::U8),
+    AttrSpec::new(34, "IFLA_PHYS_PORT_ID", AttrKind::Bytes),
+    AttrSpec::new(35, "IFLA_CARRIER_CHANGES", AttrKind::U32),
+    AttrSpec::new(36, "IFLA_PHYS_SWITCH_ID", AttrKind::Bytes),
+    AttrSpec::new(37, "IFLA_LINK_NETNSID", AttrKind::I32),
+    AttrSpec::new(38, "IFLA_PHYS_PORT_NAME", AttrKind::Str),
+    AttrSpec::new(39, "IFLA_PROTO_DOWN", AttrKind::U8),
+    AttrSpec::new(40, "IFLA_GSO_MAX_SEGS", AttrKind::U32),
+    AttrSpec::new(41, "IFLA_GSO_MAX_SIZE", AttrKind::U32),
+    AttrSpec::new(42, "IFLA_PAD", AttrKind::Bytes),
+    AttrSpec::new(43, "IFLA_XDP", AttrKind::Nested(IFLA_XDP_ATTRS)),
+    AttrSpec::new(44, "IFLA_EVENT", AttrKind::U32),
+    AttrSpec::new(45, "IFLA_NEW_NETNSID", AttrKind::I32),
+    AttrSpec::new(46, "IFLA_IF_NETNSID", AttrKind::I32),
+    AttrSpec::new(47, "IFLA_CARRIER_UP_COUNT", AttrKind::U32),
+    AttrSpec::new(48, "IFLA_CARRIER_DOWN_COUNT", AttrKind::U32),
+    AttrSpec::new(49, "IFLA_NEW_IFINDEX", AttrKind::I32),
+    AttrSpec::new(50, "IFLA_MIN_MTU", AttrKind::U32),
+    AttrSpec::new(51, "IFLA_MAX_MTU", AttrKind::U32),
+    AttrSpec::new(52, "IFLA_PROP_LIST", AttrKind::Nested(IFLA_PROP_LIST_ATTRS)),
+    AttrSpec::new(53, "IFLA_ALT_IFNAME", AttrKind::Str),
+    AttrSpec::new(54, "IFLA_PERM_ADDRESS", AttrKind::HwAddr),
+    AttrSpec::new(55, "IFLA_PROTO_DOWN_REASON", AttrKind::Bytes),
+    AttrSpec::new(56, "IFLA_PARENT_DEV_NAME", AttrKind::Str),
+    AttrSpec::new(57, "IFLA_PARENT_DEV_BUS_NAME", AttrKind::Str),
+    AttrSpec::new(58, "IFLA_GRO_MAX_SIZE", AttrKind::U32),
+    AttrSpec::new(59, "IFLA_TSO_MAX_SIZE", AttrKind::U32),
+    AttrSpec::new(60, "IFLA_TSO_MAX_SEGS", AttrKind::U32),
+    AttrSpec::new(61, "IFLA_ALLMULTI", AttrKind::U32),
+    AttrSpec::new(62, "IFLA_DEVLINK_PORT", AttrKind::Bytes),
+    AttrSpec::new(63, "IFLA_GSO_IPV4_MAX_SIZE", AttrKind::U32),
+    AttrSpec::new(64, "IFLA_GRO_IPV4_MAX_SIZE", AttrKind::U32),
+    AttrSpec::new(65, "IFLA_DPLL_PIN", AttrKind::Bytes),
+    AttrSpec::new(66, "IFLA_MAX_PACING_OFFLOAD_HORIZON", AttrKind::U32),
+    AttrSpec::new(67, "IFLA_NETNS_IMMUTABLE", AttrKind::U8),
+    AttrSpec::new(68, "IFLA_HEADROOM", AttrKind::U16),
+    AttrSpec::new(69, "IFLA_TAILROOM", AttrKind::U16),
+];
+
+impl MessageDecoder for LinkDecoder {
+    fn type_name(&self, typ: u16) -> Option<&'static str> {
+        match deserialize_repr::<RouteMessageType>(typ)? {
+            RouteMessageType::NewLink => Some("RTM_NEWLINK"),
+            RouteMessageType::DelLink => Some("RTM_DELLINK"),
+            RouteMessageType::GetLink => Some("RTM_GETLINK"),
+            RouteMessageType::SetLink => Some("RTM_SETLINK"),
+            _ => None,
+        }
+    }
+
+    fn flag_kind(&self, typ: u16) -> FlagKind {
+        match deserialize_repr::<RouteMessageType>(typ) {
+            Some(RouteMessageType::NewLink) => FlagKind::New,
+            Some(RouteMessageType::DelLink) => FlagKind::Delete,
+            Some(RouteMessageType::GetLink) => FlagKind::Get,
+            _ => FlagKind::None,
+        }
+    }
+
+    fn decode(&self, _typ: u16, payload: &[u8]) -> Result<Vec<FieldValue>> {
+        let mut reader = SliceReader::new(payload);
+        let ifinfomsg = reader.read::<InterfaceInfoMessage>()?;
+
+        let header = vec![
+            Field::text("ifi_family", &family_name(ifinfomsg.family)),
+            Field::text("ifi_type", &ifinfomsg.typ),
+            Field::text("ifi_index", &ifinfomsg.index),
+            Field::text("ifi_flags", &format!("{:#x}", ifinfomsg.flags)),
+            Field::text("ifi_change", &format!("{:#x}", ifinfomsg.change)),
+        ];
+
+        let attrs = decode_attributes(reader.take(reader.remaining())?, IFLA_ATTRS)?;
+        Ok(vec![
+            FieldValue::Struct(header),
+            FieldValue::Attributes(attrs),
+        ])
+    }
+}
@@ -1,7 +1,5 @@
-use std::net::IpAddr;
-
 use crate::{
-    bytes::{deserialize_ascii, deserialize_ip_addr, deserialize_u32},
+    bytes::{deserialize_ascii, deserialize_u32, serialize_ascii},
     Error, Result,
 };
 use derive_builder::Builder;
@@ -16,8 +14,11 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 #[derive(PartialEq, Copy, Clone, Debug, Default, Builder, Serialize, Deserialize)]
 #[builder(default, build_fn(error = "Error"))]
 pub struct InterfaceInfoMessage {
-    /// AF_UNSPEC
+    /// `AF_UNSPEC`
     pub family: u8,
+    // `__ifi_pad`, which aligns the device type
+    #[builder(setter(skip))]
+    pad: u8,
     /// Device type
     pub typ: u16,
     /// Interface index
@@ -53,145 +54,92 @@ pub struct LinkAttrHeader {
 #[derive(Debug, PartialEq, Copy, Clone, Serialize_repr, Deserialize_repr)]
 #[serde(try_from = "u16")]
 pub enum LinkAttrType {
-    Unspec,
-    Address,
-    Broadcast,
-    InterfaceName,
-    MaxTransmissionUnit,
-    Link,
-    QueueingDiscipline,
-    Stats,
-    Cost,
-    Priority,
-    Master,
-    Wireless,
-    Protinfo,
-    TransmissionQueueLen,
-    Map,
-    Weight,
-    Operstate,
-    Linkmode,
-    Linkinfo,
-    NetNsPid,
-    InterfaceAlias,
-    NumVf,
-    VfinfoList,
-    Stats64,
-    VfPorts,
-    PortSelf,
-    AfSpec,
-    Group,
-    NetNsFd,
-    ExtMask,
-    Promiscuity,
-    NumTxQueues,
-    NumRxQueues,
-    Carrier,
-    PhysPortId,
-    CarrierChanges,
-    PhysSwitchId,
-    LinkNetnsid,
-    PhysPortName,
-    ProtoDown,
-    GsoMaxSegs,
-    GsoMaxSize,
-    Pad,
-    Xdp,
-    Event,
-    NewNetnsid,
-    IfNetnsid,
-    TargetNetnsid,
-    CarrierUpCount,
-    CarrierDownCount,
-    NewInterfaceIndex,
-    MinMtu,
-    MaxMtu,
-    PropList,
-    AltInterfaceName,
-    PermAddress,
-    ProtoDownReason,
-    ParentDevName,
-    ParentDevBusName,
-    GroMaxSize,
-    TsoMaxSize,
-    TsoMaxSegs,
-    AllMulti,
+    Unspec = 0,
+    Address = 1,
+    Broadcast = 2,
+    InterfaceName = 3,
+    MaxTransmissionUnit = 4,
+    Link = 5,
+    QueueingDiscipline = 6,
+    Stats = 7,
+    Cost = 8,
+    Priority = 9,
+    Master = 10,
+    Wireless = 11,
+    Protinfo = 12,
+    TransmissionQueueLen = 13,
+    Map = 14,
+    Weight = 15,
+    Operstate = 16,
+    Linkmode = 17,
+    Linkinfo = 18,
+    NetNsPid = 19,
+    InterfaceAlias = 20,
+    NumVf = 21,
+    VfinfoList = 22,
+    Stats64 = 23,
+    VfPorts = 24,
+    PortSelf = 25,
+    AfSpec = 26,
+    Group = 27,
+    NetNsFd = 28,
+    ExtMask = 29,
+    Promiscuity = 30,
+    NumTxQueues = 31,
+    NumRxQueues = 32,
+    Carrier = 33,
+    PhysPortId = 34,
+    CarrierChanges = 35,
+    PhysSwitchId = 36,
+    LinkNetnsid = 37,
+    PhysPortName = 38,
+    ProtoDown = 39,
+    GsoMaxSegs = 40,
+    GsoMaxSize = 41,
+    Pad = 42,
+    Xdp = 43,
+    Event = 44,
+    NewNetnsid = 45,
+    /// Also known as `IFLA_TARGET_NETNSID`
+    IfNetnsid = 46,
+    CarrierUpCount = 47,
+    CarrierDownCount = 48,
+    NewInterfaceIndex = 49,
+    MinMtu = 50,
+    MaxMtu = 51,
+    PropList = 52,
+    AltInterfaceName = 53,
+    PermAddress = 54,
+    ProtoDownReason = 55,
+    ParentDevName = 56,
+    ParentDevBusName = 57,
+    GroMaxSize = 58,
+    TsoMaxSize = 59,
+    TsoMaxSegs = 60,
+    AllMulti = 61,
+}
+
+#[allow(non_upper_case_globals)]
+impl LinkAttrType {
+    /// `IFLA_TARGET_NETNSID` is an alias of `IFLA_IF_NETNSID`, so it was
+    /// never a type of its own. Its former variant shifted every type after
+    /// it by one.
+    #[deprecated(note = "use `LinkAttrType::IfNetnsid`, which has the same value")]
+    pub const TargetNetnsid: Self = Self::IfNetnsid;
 }
 
 impl From<LinkAttrType> for u16 {
     fn from(typ: LinkAttrType) -> Self {
-        match typ {
-            LinkAttrType::Unspec => 0,
-            LinkAttrType::Address => 1,
-            LinkAttrType::Broadcast => 2,
-            LinkAttrType::InterfaceName => 3,
-            LinkAttrType::MaxTransmissionUnit => 4,
-            LinkAttrType::Link => 5,
-            LinkAttrType::QueueingDiscipline => 6,
-            LinkAttrType::Stats => 7,
-            LinkAttrType::Cost => 8,
-            LinkAttrType::Priority => 9,
-            LinkAttrType::Master => 10,
-            LinkAttrType::Wireless => 11,
-            LinkAttrType::Protinfo => 12,
-            LinkAttrType::TransmissionQueueLen => 13,
-            LinkAttrType::Map => 14,
-            LinkAttrType::Weight => 15,
-            LinkAttrType::Operstate => 16,
-            LinkAttrType::Linkmode => 17,
-            LinkAttrType::Linkinfo => 18,
-            LinkAttrType::NetNsPid => 19,
-            LinkAttrType::InterfaceAlias => 20,
-            LinkAttrType::NumVf => 21,
-            LinkAttrType::VfinfoList => 22,
-            LinkAttrType::Stats64 => 23,
-            LinkAttrType::VfPorts => 24,
-            LinkAttrType::PortSelf => 25,
-            LinkAttrType::AfSpec => 26,
-            LinkAttrType::Group => 27,
-            LinkAttrType::NetNsFd => 28,
-            LinkAttrType::ExtMask => 29,
-            LinkAttrType::Promiscuity => 30,
-            LinkAttrType::NumTxQueues => 31,
-            LinkAttrType::NumRxQueues => 32,
-            LinkAttrType::Carrier => 33,
-            LinkAttrType::PhysPortId => 34,
-            LinkAttrType::CarrierChanges => 35,
-            LinkAttrType::PhysSwitchId => 36,
-            LinkAttrType::LinkNetnsid => 37,
-            LinkAttrType::PhysPortName => 38,
-            LinkAttrType::ProtoDown => 39,
-            LinkAttrType::GsoMaxSegs => 40,
-            LinkAttrType::GsoMaxSize => 41,
-            LinkAttrType::Pad => 42,
-            LinkAttrType::Xdp => 43,
-            LinkAttrType::Event => 44,
-            LinkAttrType::NewNetnsid => 45,
-            LinkAttrType::IfNetnsid => 46,
-            LinkAttrType::TargetNetnsid => 46,
-            LinkAttrType::CarrierUpCount => 47,
-            LinkAttrType::CarrierDownCount => 48,
-            LinkAttrType::NewInterfaceIndex => 49,
-            LinkAttrType::MinMtu => 50,
-            LinkAttrType::MaxMtu => 51,
-            LinkAttrType::PropList => 52,
-            LinkAttrType::AltInterfaceName => 53,
-            LinkAttrType::PermAddress => 54,
-            LinkAttrType::ProtoDownReason => 55,
-            LinkAttrType::ParentDevName => 56,
-            LinkAttrType::ParentDevBusName => 57,
-            LinkAttrType::GroMaxSize => 58,
-            LinkAttrType::TsoMaxSize => 59,
-            LinkAttrType::TsoMaxSegs => 60,
-            LinkAttrType::AllMulti => 61,
-        }
+        typ as u16
     }
 }
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum LinkAttrValue {
     Unspec,
-    Address(IpAddr),
+    /// Hardware address, like the 6 bytes of an Ethernet MAC address. Its
+    /// length depends on the device type, and it isn't an IP address.
+    Address(Vec<u8>),
     Broadcast(Vec<u8>),
     InterfaceName(String),
     MaxTransmissionUnit(Vec<u8>),
@@ -200,7 +148,7 @@ pub enum LinkAttrValue {
     Stats(Vec<u8>),
     Cost(Vec<u8>),
     Priority(Vec<u8>),
-    Master(Vec<u8>),
+    Master(u32),
     Wireless(Vec<u8>),
     Protinfo(Vec<u8>),
     TransmissionQueueLen(Vec<u8>),
@@ -237,7 +185,6 @@ pub enum LinkAttrValue {
     Event(Vec<u8>),
     NewNetnsid(Vec<u8>),
     IfNetnsid(Vec<u8>),
-    TargetNetnsid(Vec<u8>),
     CarrierUpCount(Vec<u8>),
     CarrierDownCount(Vec<u8>),
     NewInterfaceIndex(Vec<u8>),
@@ -245,7 +192,8 @@ pub enum LinkAttrValue {
     MaxMtu(Vec<u8>),
     PropList(Vec<u8>),
     AltInterfaceName(String),
-    PermAddress(IpAddr),
+    /// Permanent hardware address, see [`LinkAttrValue::Address`]
+    PermAddress(Vec<u8>),
     ProtoDownReason(Vec<u8>),
     ParentDevName(String),
     ParentDevBusName(String),
@@ -263,7 +211,7 @@ impl LinkAttrValue {
                 Ok(Self::Unspec)
             },
             LinkAttrType::Address => {
-                deserialize_ip_addr(payload).map(Self::Address)
+                Ok(Self::Address(payload.to_vec()))
             },
             LinkAttrType::Broadcast => {
                 Ok(Self::Broadcast(payload.to_vec()))
@@ -290,7 +238,7 @@ impl LinkAttrValue {
                 Ok(Self::Priority(payload.to_vec()))
             },
             LinkAttrType::Master => {
-                Ok(Self::Master(payload.to_vec()))
+                deserialize_u32(payload).map(Self::Master)
             },
             LinkAttrType::Wireless => {
                 Ok(Self::Wireless(payload.to_vec()))
@@ -400,9 +348,6 @@ impl LinkAttrValue {
             LinkAttrType::IfNetnsid => {
                 Ok(Self::IfNetnsid(payload.to_vec()))
             },
-            LinkAttrType::TargetNetnsid => {
-                Ok(Self::TargetNetnsid(payload.to_vec()))
-            },
             LinkAttrType::CarrierUpCount => {
                 Ok(Self::CarrierUpCount(payload.to_vec()))
             },
@@ -425,7 +370,7 @@ impl LinkAttrValue {
                 Ok(Self::AltInterfaceName(deserialize_ascii(payload)))
             },
             LinkAttrType::PermAddress => {
-                deserialize_ip_addr(payload).map(Self::PermAddress)
+                Ok(Self::PermAddress(payload.to_vec()))
             },
             LinkAttrType::ProtoDownReason => {
                 Ok(Self::ProtoDownReason(payload.to_vec()))
@@ -451,3 +396,197 @@ impl LinkAttrValue {
         }
     }
 }
+
+#[rustfmt::skip]
+impl LinkAttrValue {
+    pub(crate) fn serialize(&self) -> (LinkAttrType, Vec<u8>) {
+        match self {
+            Self::Unspec => {
+                (LinkAttrType::Unspec, vec![])
+            },
+            Self::Address(bytes) => {
+                (LinkAttrType::Address, bytes.clone())
+            },
+            Self::Broadcast(bytes) => {
+                (LinkAttrType::Broadcast, bytes.clone())
+            },
+            Self::InterfaceName(name) => {
+                (LinkAttrType::InterfaceName, serialize_ascii(name))
+            },
+            Self::MaxTransmissionUnit(bytes) => {
+                (LinkAttrType::MaxTransmissionUnit, bytes.clone())
+            },
+            Self::Link(bytes) => {
+                (LinkAttrType::Link, bytes.clone())
+            },
+            Self::QueueingDiscipline(bytes) => {
+                (LinkAttrType::QueueingDiscipline, bytes.clone())
+            },
+            Self::Stats(bytes) => {
+                (LinkAttrType::Stats, bytes.clone())
+            },
+            Self::Cost(bytes) => {
+                (LinkAttrType::Cost, bytes.clone())
+            },
+            Self::Priority(bytes) => {
+                (LinkAttrType::Priority, bytes.clone())
+            },
+            Self::Master(index) => {
+                (LinkAttrType::Master, index.to_le_bytes().to_vec())
+            },
+            Self::Wireless(bytes) => {
+                (LinkAttrType::Wireless, bytes.clone())
+            },
+            Self::Protinfo(bytes) => {
+                (LinkAttrType::Protinfo, bytes.clone())
+            },
+            Self::TransmissionQueueLen(bytes) => {
+                (LinkAttrType::TransmissionQueueLen, bytes.clone())
+            },
+            Self::Map(bytes) => {
+                (LinkAttrType::Map, bytes.clone())
+            },
+            Self::Weight(bytes) => {
+                (LinkAttrType::Weight, bytes.clone())
+            },
+            Self::Operstate(bytes) => {
+                (LinkAttrType::Operstate, bytes.clone())
+            },
+            Self::Linkmode(bytes) => {
+                (LinkAttrType::Linkmode, bytes.clone())
+            },
+            Self::Linkinfo(bytes) => {
+                (LinkAttrType::Linkinfo, bytes.clone())
+            },
+            Self::NetNsPid(bytes) => {
+                (LinkAttrType::NetNsPid, bytes.clone())
+            },
+            Self::InterfaceAlias(bytes) => {
+                (LinkAttrType::InterfaceAlias, bytes.clone())
+            },
+            Self::NumVf(bytes) => {
+                (LinkAttrType::NumVf, bytes.clone())
+            },
+            Self::VfinfoList(bytes) => {
+                (LinkAttrType::VfinfoList, bytes.clone())
+            },
+            Self::Stats64(bytes) => {
+                (LinkAttrType::Stats64, bytes.clone())
+            },
+            Self::VfPorts(bytes) => {
+                (LinkAttrType::VfPorts, bytes.clone())
+            },
+            Self::PortSelf(bytes) => {
+                (LinkAttrType::PortSelf, bytes.clone())
+            },
+            Self::AfSpec(bytes) => {
+                (LinkAttrType::AfSpec, bytes.clone())
+            },
+            Self::Group(bytes) => {
+                (LinkAttrType::Group, bytes.clone())
+            },
+            Self::NetNsFd(bytes) => {
+                (LinkAttrType::NetNsFd, bytes.clone())
+            },
+            Self::ExtMask(bytes) => {
+                (LinkAttrType::ExtMask, bytes.clone())
+            },
+            Self::Promiscuity(value) => {
+                (LinkAttrType::Promiscuity, value.to_le_bytes().to_vec())
+            },
+            Self::NumTxQueues(bytes) => {
+                (LinkAttrType::NumTxQueues, bytes.clone())
+            },
+            Self::NumRxQueues(bytes) => {
+                (LinkAttrType::NumRxQueues, bytes.clone())
+            },
+            Self::Carrier(bytes) => {
+                (LinkAttrType::Carrier, bytes.clone())
+            },
+            Self::PhysPortId(bytes) => {
+                (LinkAttrType::PhysPortId, bytes.clone())
+            },
+            Self::CarrierChanges(bytes) => {
+                (LinkAttrType::CarrierChanges, bytes.clone())
+            },
+            Self::PhysSwitchId(bytes) => {
+                (LinkAttrType::PhysSwitchId, bytes.clone())
+            },
+            Self::LinkNetnsid(bytes) => {
+                (LinkAttrType::LinkNetnsid, bytes.clone())
+            },
+            Self::PhysPortName(name) => {
+                (LinkAttrType::PhysPortName, serialize_ascii(name))
+            },
+            Self::ProtoDown(bytes) => {
+                (LinkAttrType::ProtoDown, bytes.clone())
+            },
+            Self::GsoMaxSegs(bytes) => {
+                (LinkAttrType::GsoMaxSegs, bytes.clone())
+            },
+            Self::GsoMaxSize(bytes) => {
+                (LinkAttrType::GsoMaxSize, bytes.clone())
+            },
+            Self::Pad(bytes) => {
+                (LinkAttrType::Pad, bytes.clone())
+            },
+            Self::Xdp(bytes) => {
+                (LinkAttrType::Xdp, bytes.clone())
+            },
+            Self::Event(bytes) => {
+                (LinkAttrType::Event, bytes.clone())
+            },
+            Self::NewNetnsid(bytes) => {
+                (LinkAttrType::NewNetnsid, bytes.clone())
+            },
+            Self::IfNetnsid(bytes) => {
+                (LinkAttrType::IfNetnsid, bytes.clone())
+            },
+            Self::CarrierUpCount(bytes) => {
+                (LinkAttrType::CarrierUpCount, bytes.clone())
+            },
+            Self::CarrierDownCount(bytes) => {
+                (LinkAttrType::CarrierDownCount, bytes.clone())
+            },
+            Self::NewInterfaceIndex(bytes) => {
+                (LinkAttrType::NewInterfaceIndex, bytes.clone())
+            },
+            Self::MinMtu(bytes) => {
+                (LinkAttrType::MinMtu, bytes.clone())
+            },
+            Self::MaxMtu(bytes) => {
+                (LinkAttrType::MaxMtu, bytes.clone())
+            },
+            Self::PropList(bytes) => {
+                (LinkAttrType::PropList, bytes.clone())
+            },
+            Self::AltInterfaceName(name) => {
+                (LinkAttrType::AltInterfaceName, serialize_ascii(name))
+            },
+            Self::PermAddress(bytes) => {
+                (LinkAttrType::PermAddress, bytes.clone())
+            },
+            Self::ProtoDownReason(bytes) => {
+                (LinkAttrType::ProtoDownReason, bytes.clone())
+            },
+            Self::ParentDevName(name) => {
+                (LinkAttrType::ParentDevName, serialize_ascii(name))
+            },
+            Self::ParentDevBusName(name) => {
+                (LinkAttrType::ParentDevBusName, serialize_ascii(name))
+            },
+            Self::GroMaxSize(bytes) => {
+                (LinkAttrType::GroMaxSize, bytes.clone())
+            },
+            Self::TsoMaxSize(bytes) => {
+                (LinkAttrType::TsoMaxSize, bytes.clone())
+            },
+            Self::TsoMaxSegs(bytes) => {
+                (LinkAttrType::TsoMaxSegs, bytes.clone())
+            },
+            Self::AllMulti(bytes) => {
+                (LinkAttrType::AllMulti, bytes.clone())
+            },
+        }
+    }
+}
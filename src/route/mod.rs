@@ -1,6 +1,25 @@
 pub mod addr;
 pub mod link;
 pub mod route;
+pub mod rule;
+
+pub mod capabilities;
+pub use capabilities::*;
+
+pub mod family;
+pub use family::*;
+
+pub mod prefix;
+pub use prefix::*;
+
+pub mod mpls;
+pub use mpls::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod decode;
+pub use decode::*;
 
 mod constants;
 pub use constants::*;
@@ -0,0 +1,148 @@
+use crate::{Error, Result};
+use serde::Serialize;
+use std::fmt;
+
+/// An MPLS label, which is what [`crate::route::AddressFamily::Mpls`] routes
+/// match on and what they push onto packets.
+///
+/// ```rust
+/// use netlink::route::MplsLabel;
+///
+/// # fn main() -> netlink::Result<()> {
+/// let label = MplsLabel::new(100)?;
+/// assert_eq!(label.value(), 100);
+///
+/// // Labels are 20 bits
+/// assert!(MplsLabel::new(1 << 20).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize)]
+pub struct MplsLabel(u32);
+
+impl MplsLabel {
+    /// The largest label
+    pub const MAX: u32 = (1 << 20) - 1;
+
+    /// # Errors
+    ///
+    /// Returns [`Error::ErrMplsLabel`] if `label` doesn't fit in 20 bits.
+    pub fn new(label: u32) -> Result<Self> {
+        if label > Self::MAX {
+            return Err(Error::ErrMplsLabel(label));
+        }
+        Ok(Self(label))
+    }
+
+    #[must_use]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for MplsLabel {
+    type Error = Error;
+
+    fn try_from(label: u32) -> Result<Self> {
+        Self::new(label)
+    }
+}
+
+impl From<MplsLabel> for u32 {
+    fn from(label: MplsLabel) -> Self {
+        label.0
+    }
+}
+
+impl fmt::Display for MplsLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Label stack entries hold the label, the traffic class, the bottom of stack
+// flag and the TTL, in network byte order. See `struct mpls_label` in
+// https://github.com/torvalds/linux/blob/master/include/uapi/linux/mpls.h
+const LABEL_SHIFT: u32 = 12;
+const BOTTOM_OF_STACK: u32 = 1 << 8;
+
+/// Decode a label stack, like `RTA_NEWDST`, ignoring the traffic class and
+/// TTL of the entries.
+pub(crate) fn deserialize_labels(payload: &[u8]) -> Result<Vec<MplsLabel>> {
+    let entries = payload.chunks(4);
+    let mut labels = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let entry = <[u8; 4]>::try_from(entry).map_err(|_| Error::ErrUnexpectedEof)?;
+        let entry = u32::from_be_bytes(entry);
+        labels.push(MplsLabel(entry >> LABEL_SHIFT));
+
+        if entry & BOTTOM_OF_STACK != 0 {
+            break;
+        }
+    }
+
+    Ok(labels)
+}
+
+/// Encode a label stack, with the bottom of stack flag on the last label and
+/// the traffic class and TTL left to the kernel.
+pub(crate) fn serialize_labels(labels: &[MplsLabel]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(labels.len() * 4);
+
+    for (i, label) in labels.iter().enumerate() {
+        let mut entry = label.0 << LABEL_SHIFT;
+        if i == labels.len() - 1 {
+            entry |= BOTTOM_OF_STACK;
+        }
+        bytes.extend_from_slice(&entry.to_be_bytes());
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_20_bits() -> Result<()> {
+        assert_eq!(MplsLabel::new(MplsLabel::MAX)?.value(), (1 << 20) - 1);
+        assert!(matches!(
+            MplsLabel::new(MplsLabel::MAX + 1),
+            Err(Error::ErrMplsLabel(label)) if label == 1 << 20
+        ));
+        assert!(MplsLabel::try_from(u32::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn last_label_is_bottom_of_stack() -> Result<()> {
+        let labels = [MplsLabel::new(200)?, MplsLabel::new(300)?];
+        let bytes = serialize_labels(&labels);
+        assert_eq!(bytes, [0x00, 0x0c, 0x80, 0x00, 0x00, 0x12, 0xc1, 0x00]);
+        assert_eq!(deserialize_labels(&bytes)?, labels);
+
+        let max = [MplsLabel::new(MplsLabel::MAX)?];
+        assert_eq!(serialize_labels(&max), [0xff, 0xff, 0xf1, 0x00]);
+        assert_eq!(serialize_labels(&[]), Vec::<u8>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn labels_after_bottom_of_stack_are_ignored() -> Result<()> {
+        // Label 100 with the bottom of stack flag, a traffic class of 5 and
+        // a TTL of 64, followed by padding
+        let bytes = [0x00, 0x06, 0x4b, 0x40, 0x00, 0x0c, 0x80, 0x00];
+        assert_eq!(deserialize_labels(&bytes)?, [MplsLabel::new(100)?]);
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_label_is_rejected() {
+        assert!(matches!(
+            deserialize_labels(&[0x00, 0x06, 0x41]),
+            Err(Error::ErrUnexpectedEof)
+        ));
+    }
+}
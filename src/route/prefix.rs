@@ -0,0 +1,279 @@
+use crate::route::AddressFamily;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// An IP address and a prefix length, like `10.0.0.0/8`. This is how routes
+/// describe their destination and source.
+///
+/// ```rust
+/// use netlink::route::IpPrefix;
+/// use std::net::Ipv4Addr;
+///
+/// # fn main() -> netlink::Result<()> {
+/// let prefix: IpPrefix = "10.0.0.0/8".parse()?;
+///
+/// assert_eq!(prefix.addr(), Ipv4Addr::new(10, 0, 0, 0));
+/// assert_eq!(prefix.prefix_len(), 8);
+/// assert_eq!(prefix.to_string(), "10.0.0.0/8");
+///
+/// // A bare address is a host prefix
+/// assert_eq!(IpPrefix::from(Ipv4Addr::new(10, 0, 0, 1)).prefix_len(), 32);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// With the `ipnet` feature, it converts to and from [`ipnet::IpNet`].
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize)]
+pub struct IpPrefix {
+    addr: IpAddr,
+    len: u8,
+}
+
+impl IpPrefix {
+    /// # Errors
+    ///
+    /// Returns [`Error::ErrPrefixLength`] if `len` is longer than the address.
+    pub fn new<A: Into<IpAddr>>(addr: A, len: u8) -> Result<Self> {
+        let addr = addr.into();
+        if len > max_len(&addr) {
+            return Err(Error::ErrPrefixLength(len));
+        }
+        Ok(Self { addr, len })
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    #[must_use]
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    #[must_use]
+    pub fn family(&self) -> AddressFamily {
+        AddressFamily::of(&self.addr)
+    }
+
+    /// Whether this is a single address, like `10.0.0.1/32`.
+    #[must_use]
+    pub fn is_host(&self) -> bool {
+        self.len == max_len(&self.addr)
+    }
+
+    /// The prefix with the bits after the prefix length cleared, like
+    /// `10.0.0.0/8` for `10.1.2.3/8`.
+    #[must_use]
+    pub fn network(&self) -> Self {
+        let addr = match self.addr {
+            IpAddr::V4(addr) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.len));
+                IpAddr::V4(Ipv4Addr::from(u32::from(addr) & mask.unwrap_or(0)))
+            }
+            IpAddr::V6(addr) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.len));
+                IpAddr::V6(Ipv6Addr::from(u128::from(addr) & mask.unwrap_or(0)))
+            }
+        };
+        Self {
+            addr,
+            len: self.len,
+        }
+    }
+}
+
+fn max_len(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+impl From<IpAddr> for IpPrefix {
+    fn from(addr: IpAddr) -> Self {
+        let len = max_len(&addr);
+        Self { addr, len }
+    }
+}
+
+impl From<Ipv4Addr> for IpPrefix {
+    fn from(addr: Ipv4Addr) -> Self {
+        IpAddr::V4(addr).into()
+    }
+}
+
+impl From<Ipv6Addr> for IpPrefix {
+    fn from(addr: Ipv6Addr) -> Self {
+        IpAddr::V6(addr).into()
+    }
+}
+
+impl fmt::Display for IpPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+impl FromStr for IpPrefix {
+    type Err = Error;
+
+    /// Parse a prefix like `10.0.0.0/8`, or a bare address as a host prefix.
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || Error::ErrParsePrefix(value.to_string());
+
+        let Some((addr, len)) = value.split_once('/') else {
+            return value
+                .parse::<IpAddr>()
+                .map(Self::from)
+                .map_err(|_| invalid());
+        };
+
+        let addr = addr.parse::<IpAddr>().map_err(|_| invalid())?;
+        let len = len.parse::<u8>().map_err(|_| invalid())?;
+        Self::new(addr, len)
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::IpNet> for IpPrefix {
+    fn from(net: ipnet::IpNet) -> Self {
+        Self {
+            addr: net.addr(),
+            len: net.prefix_len(),
+        }
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::Ipv4Net> for IpPrefix {
+    fn from(net: ipnet::Ipv4Net) -> Self {
+        ipnet::IpNet::V4(net).into()
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::Ipv6Net> for IpPrefix {
+    fn from(net: ipnet::Ipv6Net) -> Self {
+        ipnet::IpNet::V6(net).into()
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<IpPrefix> for ipnet::IpNet {
+    fn from(prefix: IpPrefix) -> Self {
+        // The length was checked when the prefix was created
+        match prefix.addr {
+            IpAddr::V4(addr) => ipnet::Ipv4Net::new(addr, prefix.len).map(Self::V4),
+            IpAddr::V6(addr) => ipnet::Ipv6Net::new(addr, prefix.len).map(Self::V6),
+        }
+        .expect("prefix length is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_length_is_bounded_by_the_address() -> Result<()> {
+        assert_eq!(IpPrefix::new(Ipv4Addr::UNSPECIFIED, 32)?.prefix_len(), 32);
+        assert!(matches!(
+            IpPrefix::new(Ipv4Addr::UNSPECIFIED, 33),
+            Err(Error::ErrPrefixLength(33))
+        ));
+
+        assert_eq!(IpPrefix::new(Ipv6Addr::UNSPECIFIED, 128)?.prefix_len(), 128);
+        assert!(matches!(
+            IpPrefix::new(Ipv6Addr::UNSPECIFIED, 129),
+            Err(Error::ErrPrefixLength(129))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn host_bits_are_masked() -> Result<()> {
+        let cases = [
+            ("10.1.2.3/8", "10.0.0.0/8"),
+            ("10.1.2.3/0", "0.0.0.0/0"),
+            ("10.1.2.3/32", "10.1.2.3/32"),
+            ("192.0.2.255/25", "192.0.2.128/25"),
+            ("2001:db8:1:2::1/48", "2001:db8:1::/48"),
+            ("2001:db8::1/0", "::/0"),
+            ("2001:db8::1/128", "2001:db8::1/128"),
+            ("2001:db8::ffff/127", "2001:db8::fffe/127"),
+        ];
+        for (prefix, network) in cases {
+            let prefix = prefix.parse::<IpPrefix>()?;
+            assert_eq!(prefix.network().to_string(), network);
+        }
+
+        // Masking is left to network(), so the address is kept as given
+        let prefix = "10.1.2.3/8".parse::<IpPrefix>()?;
+        assert_eq!(prefix.addr(), Ipv4Addr::new(10, 1, 2, 3));
+        assert!(!prefix.is_host());
+        Ok(())
+    }
+
+    #[test]
+    fn bare_address_is_a_host_prefix() -> Result<()> {
+        let v4 = "192.0.2.1".parse::<IpPrefix>()?;
+        assert_eq!(v4, IpPrefix::new(Ipv4Addr::new(192, 0, 2, 1), 32)?);
+        assert!(v4.is_host());
+
+        let v6 = "2001:db8::1".parse::<IpPrefix>()?;
+        assert_eq!(v6.prefix_len(), 128);
+        assert_eq!(v6.family(), AddressFamily::Inet6);
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_prefixes_are_rejected() {
+        for value in [
+            "",
+            "/8",
+            "10.0.0.0/",
+            "10.0.0/8",
+            "10.0.0.0/x",
+            "10.0.0.0/-1",
+            "10.0.0.0/8/8",
+        ] {
+            assert!(
+                matches!(value.parse::<IpPrefix>(), Err(Error::ErrParsePrefix(ref v)) if v == value),
+                "{value:?} was accepted"
+            );
+        }
+
+        // A length that parses, but is too long for the address
+        assert!(matches!(
+            "10.0.0.0/33".parse::<IpPrefix>(),
+            Err(Error::ErrPrefixLength(33))
+        ));
+        assert!(matches!(
+            "::/256".parse::<IpPrefix>(),
+            Err(Error::ErrParsePrefix(_))
+        ));
+    }
+
+    #[cfg(feature = "ipnet")]
+    #[test]
+    fn ipnet_conversions_round_trip() -> Result<()> {
+        let v4 = "10.1.2.3/8"
+            .parse::<ipnet::Ipv4Net>()
+            .map_err(|_| Error::ErrValueConversion)?;
+        let prefix = IpPrefix::from(v4);
+        assert_eq!(prefix.to_string(), "10.1.2.3/8");
+        assert_eq!(ipnet::IpNet::from(prefix), ipnet::IpNet::V4(v4));
+
+        let v6 = "2001:db8::/32"
+            .parse::<ipnet::Ipv6Net>()
+            .map_err(|_| Error::ErrValueConversion)?;
+        let prefix = IpPrefix::from(v6);
+        assert_eq!(prefix, "2001:db8::/32".parse()?);
+        assert_eq!(ipnet::IpNet::from(prefix), ipnet::IpNet::V6(v6));
+        Ok(())
+    }
+}
@@ -0,0 +1,905 @@
+use super::kinds::impl_raw_enum;
+use crate::bytes::{deserialize_ascii, deserialize_ip_addr, deserialize_u16_be, deserialize_u32};
+use crate::bytes::{deserialize_u64_be, serialize_ip_addr, SliceReader};
+use crate::bytes::{deserialize_u8, push_attribute, push_nested_attribute, serialize_ascii};
+use crate::route::{deserialize_labels, serialize_labels, MplsLabel};
+use crate::{Error, Result};
+use derive_builder::Builder;
+use serde::Serialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::os::fd::RawFd;
+
+/// Type of a lightweight tunnel, from `RTA_ENCAP_TYPE`. See
+/// `LWTUNNEL_ENCAP_*` in
+/// [`lwtunnel.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/lwtunnel.h).
+#[derive(Copy, Clone, Debug, Serialize)]
+pub enum LwtEncapType {
+    Unspec,
+    Mpls,
+    Ip,
+    Ila,
+    Ip6,
+    Seg6,
+    Bpf,
+    Seg6Local,
+    Rpl,
+    Ioam6,
+    Xfrm,
+    Other(u16),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(LwtEncapType, u16, Other, {
+    Unspec = 0 => "none",
+    Mpls = 1 => "mpls",
+    Ip = 2 => "ip",
+    Ila = 3 => "ila",
+    Ip6 = 4 => "ip6",
+    Seg6 = 5 => "seg6",
+    Bpf = 6 => "bpf",
+    Seg6Local = 7 => "seg6local",
+    Rpl = 8 => "rpl",
+    Ioam6 = 9 => "ioam6",
+    Xfrm = 10 => "xfrm",
+});
+
+/// Lightweight tunnel encapsulation of a route, like `ip route ... encap`.
+/// Packets routed through it are encapsulated, or processed, before they
+/// are sent.
+///
+/// ```rust
+/// use netlink::route::route::{LwtEncap, Route, Seg6Encap, Seg6Mode};
+/// use netlink::route::IpPrefix;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // ip route add 10.0.0.0/24 encap seg6 mode encap segs fc00::1,fc00::2 dev eth0
+/// let encap = Seg6Encap::builder()
+///     .mode(Seg6Mode::Encap)
+///     .segments(vec!["fc00::1".parse()?, "fc00::2".parse()?])
+///     .build()?;
+///
+/// let route = Route::builder()
+///     .dest("10.0.0.0/24".parse::<IpPrefix>()?)
+///     .output_interface_index(2)
+///     .encap(LwtEncap::Seg6(encap))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub enum LwtEncap {
+    /// Push MPLS labels, like `encap mpls 100/200`
+    Mpls(MplsEncap),
+    /// Metadata for an IPv4 tunnel device in external mode, like
+    /// `encap ip id 10 dst 192.0.2.1`
+    Ip(IpTunnelEncap),
+    /// Metadata for an IPv6 tunnel device in external mode
+    Ip6(IpTunnelEncap),
+    /// Add an IPv6 segment routing header, like `encap seg6 mode encap`
+    Seg6(Seg6Encap),
+    /// Process an IPv6 segment locally, like `encap seg6local action End`
+    Seg6Local(Seg6LocalEncap),
+    /// Run BPF programs, like `encap bpf xmit obj prog.o`
+    Bpf(BpfEncap),
+    /// Tunnels this library doesn't decode, with their raw attributes
+    Other { typ: LwtEncapType, data: Vec<u8> },
+}
+
+impl LwtEncap {
+    /// The value of `RTA_ENCAP_TYPE` for this encapsulation.
+    #[must_use]
+    pub fn typ(&self) -> LwtEncapType {
+        match self {
+            Self::Mpls(_) => LwtEncapType::Mpls,
+            Self::Ip(_) => LwtEncapType::Ip,
+            Self::Ip6(_) => LwtEncapType::Ip6,
+            Self::Seg6(_) => LwtEncapType::Seg6,
+            Self::Seg6Local(_) => LwtEncapType::Seg6Local,
+            Self::Bpf(_) => LwtEncapType::Bpf,
+            Self::Other { typ, .. } => *typ,
+        }
+    }
+}
+
+/// MPLS labels pushed onto the packets of a route. See
+/// [`mpls_iptunnel.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/mpls_iptunnel.h).
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct MplsEncap {
+    /// Labels, the outermost first
+    pub labels: Vec<MplsLabel>,
+    /// TTL of the labels, instead of the one of the packet
+    pub ttl: Option<u8>,
+}
+
+impl MplsEncap {
+    #[must_use]
+    pub fn builder() -> MplsEncapBuilder {
+        MplsEncapBuilder::default()
+    }
+}
+
+/// Tunnel metadata for packets sent through a tunnel device in external
+/// mode, like VXLAN or GRE with `external`. See `LWTUNNEL_IP_*` and
+/// `LWTUNNEL_IP6_*` in
+/// [`lwtunnel.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/lwtunnel.h).
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct IpTunnelEncap {
+    /// Tunnel key, like the VNI of VXLAN
+    pub id: Option<u64>,
+    pub dest: Option<IpAddr>,
+    pub source: Option<IpAddr>,
+    /// TTL, or hop limit for IPv6
+    pub ttl: Option<u8>,
+    /// TOS, or traffic class for IPv6
+    pub tos: Option<u8>,
+    /// `TUNNEL_*` flags, like `TUNNEL_CSUM`
+    pub flags: Option<u16>,
+}
+
+impl IpTunnelEncap {
+    #[must_use]
+    pub fn builder() -> IpTunnelEncapBuilder {
+        IpTunnelEncapBuilder::default()
+    }
+}
+
+/// How [`Seg6Encap`] adds the segment routing header.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum Seg6Mode {
+    /// Insert the header in the IPv6 packet
+    Inline,
+    /// Encapsulate the packet in an outer IPv6 header with the segments
+    #[default]
+    Encap,
+    /// Encapsulate the Ethernet frame of the packet
+    L2Encap,
+    /// Like [`Seg6Mode::Encap`], with the first segment only in the
+    /// destination of the outer header
+    EncapReduced,
+    /// Like [`Seg6Mode::L2Encap`], with the first segment only in the
+    /// destination of the outer header
+    L2EncapReduced,
+    Other(i32),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(Seg6Mode, i32, Other, {
+    Inline = 0 => "inline",
+    Encap = 1 => "encap",
+    L2Encap = 2 => "l2encap",
+    EncapReduced = 3 => "encap.red",
+    L2EncapReduced = 4 => "l2encap.red",
+});
+
+/// IPv6 segments added to the packets of a route. See
+/// [`seg6_iptunnel.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/seg6_iptunnel.h).
+///
+/// In [`Seg6Mode::Inline`], the kernel adds the original destination of the
+/// packet as the last segment, which isn't listed here. HMAC TLVs aren't
+/// supported.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct Seg6Encap {
+    pub mode: Seg6Mode,
+    /// Segments in the order the packet visits them, at most 127, or 126
+    /// with [`Seg6Mode::Inline`]
+    pub segments: Vec<Ipv6Addr>,
+}
+
+impl Seg6Encap {
+    #[must_use]
+    pub fn builder() -> Seg6EncapBuilder {
+        Seg6EncapBuilder::default()
+    }
+}
+
+/// Behavior of a local IPv6 segment, with the names of
+/// [RFC 8986](https://www.rfc-editor.org/rfc/rfc8986).
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum Seg6LocalAction {
+    #[default]
+    Unspec,
+    /// Continue with the next segment
+    End,
+    /// Continue with the next segment, through a layer 3 cross-connect
+    EndX,
+    /// Continue with the next segment, looked up in a table
+    EndT,
+    EndDx2,
+    EndDx6,
+    EndDx4,
+    /// Decapsulate, and look up the inner IPv6 packet in a table
+    EndDt6,
+    /// Decapsulate, and look up the inner IPv4 packet in a table
+    EndDt4,
+    /// Insert a segment routing header
+    EndB6,
+    /// Encapsulate with a segment routing header
+    EndB6Encaps,
+    EndBm,
+    EndS,
+    EndAs,
+    EndAm,
+    EndBpf,
+    /// Decapsulate, and look up the inner packet in a VRF
+    EndDt46,
+    Other(u32),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(Seg6LocalAction, u32, Other, {
+    Unspec = 0 => "unspec",
+    End = 1 => "End",
+    EndX = 2 => "End.X",
+    EndT = 3 => "End.T",
+    EndDx2 = 4 => "End.DX2",
+    EndDx6 = 5 => "End.DX6",
+    EndDx4 = 6 => "End.DX4",
+    EndDt6 = 7 => "End.DT6",
+    EndDt4 = 8 => "End.DT4",
+    EndB6 = 9 => "End.B6",
+    EndB6Encaps = 10 => "End.B6.Encaps",
+    EndBm = 11 => "End.BM",
+    EndS = 12 => "End.S",
+    EndAs = 13 => "End.AS",
+    EndAm = 14 => "End.AM",
+    EndBpf = 15 => "End.BPF",
+    EndDt46 = 16 => "End.DT46",
+});
+
+/// Processing of packets sent to a local IPv6 segment. Which of the
+/// parameters are needed depends on the action. See
+/// [`seg6_local.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/seg6_local.h).
+///
+/// ```rust
+/// use netlink::route::route::{Seg6LocalAction, Seg6LocalEncap};
+///
+/// # fn main() -> netlink::Result<()> {
+/// // ip -6 route add fc00::100/128 encap seg6local action End.DT6 table 100 dev eth0
+/// let encap = Seg6LocalEncap::builder()
+///     .action(Seg6LocalAction::EndDt6)
+///     .table(100u32)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct Seg6LocalEncap {
+    pub action: Seg6LocalAction,
+    /// Segments of the header added by [`Seg6LocalAction::EndB6`] and
+    /// [`Seg6LocalAction::EndB6Encaps`], in the order the packet visits them.
+    /// Like [`Seg6Encap::segments`], at most 127, or 126 with `EndB6`.
+    pub segments: Vec<Ipv6Addr>,
+    pub table: Option<u32>,
+    /// VRF table of [`Seg6LocalAction::EndDt4`], [`Seg6LocalAction::EndDt6`]
+    /// and [`Seg6LocalAction::EndDt46`]
+    pub vrf_table: Option<u32>,
+    /// IPv4 next hop of [`Seg6LocalAction::EndDx4`]
+    pub nh4: Option<Ipv4Addr>,
+    /// IPv6 next hop of [`Seg6LocalAction::EndX`] and [`Seg6LocalAction::EndDx6`]
+    pub nh6: Option<Ipv6Addr>,
+    pub input_interface_index: Option<i32>,
+    pub output_interface_index: Option<i32>,
+    /// Program of [`Seg6LocalAction::EndBpf`]
+    pub bpf: Option<BpfProgram>,
+}
+
+impl Seg6LocalEncap {
+    #[must_use]
+    pub fn builder() -> Seg6LocalEncapBuilder {
+        Seg6LocalEncapBuilder::default()
+    }
+}
+
+/// A BPF program attached to a route. The kernel takes the program from a
+/// file descriptor, and only reports its name.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize)]
+pub struct BpfProgram {
+    /// File descriptor of the loaded program, only used when installing it
+    pub fd: Option<RawFd>,
+    pub name: String,
+}
+
+impl BpfProgram {
+    #[must_use]
+    pub fn new<S: Into<String>>(fd: RawFd, name: S) -> Self {
+        Self {
+            fd: Some(fd),
+            name: name.into(),
+        }
+    }
+}
+
+impl fmt::Display for BpfProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// BPF programs run on the packets of a route. See
+/// [`lwtunnel.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/lwtunnel.h).
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct BpfEncap {
+    /// Run on packets received through the route
+    pub input: Option<BpfProgram>,
+    /// Run on packets sent through the route
+    pub output: Option<BpfProgram>,
+    /// Run on packets just before they are transmitted, which may push
+    /// headers
+    pub xmit: Option<BpfProgram>,
+    /// Room to reserve for the headers pushed by `xmit`
+    pub xmit_headroom: Option<u32>,
+}
+
+impl BpfEncap {
+    #[must_use]
+    pub fn builder() -> BpfEncapBuilder {
+        BpfEncapBuilder::default()
+    }
+}
+
+// Attributes nested in RTA_ENCAP, which are numbered per type of tunnel
+const MPLS_IPTUNNEL_DST: u16 = 1;
+const MPLS_IPTUNNEL_TTL: u16 = 2;
+
+const LWTUNNEL_IP_ID: u16 = 1;
+const LWTUNNEL_IP_DST: u16 = 2;
+const LWTUNNEL_IP_SRC: u16 = 3;
+const LWTUNNEL_IP_TTL: u16 = 4;
+const LWTUNNEL_IP_TOS: u16 = 5;
+const LWTUNNEL_IP_FLAGS: u16 = 6;
+
+const SEG6_IPTUNNEL_SRH: u16 = 1;
+
+const SEG6_LOCAL_ACTION: u16 = 1;
+const SEG6_LOCAL_SRH: u16 = 2;
+const SEG6_LOCAL_TABLE: u16 = 3;
+const SEG6_LOCAL_NH4: u16 = 4;
+const SEG6_LOCAL_NH6: u16 = 5;
+const SEG6_LOCAL_IIF: u16 = 6;
+const SEG6_LOCAL_OIF: u16 = 7;
+const SEG6_LOCAL_BPF: u16 = 8;
+const SEG6_LOCAL_VRFTABLE: u16 = 9;
+
+const LWT_BPF_IN: u16 = 1;
+const LWT_BPF_OUT: u16 = 2;
+const LWT_BPF_XMIT: u16 = 3;
+const LWT_BPF_XMIT_HEADROOM: u16 = 4;
+
+// Also the layout of SEG6_LOCAL_BPF
+const LWT_BPF_PROG_FD: u16 = 1;
+const LWT_BPF_PROG_NAME: u16 = 2;
+
+/// Decode `RTA_ENCAP`, whose attributes depend on `RTA_ENCAP_TYPE`.
+pub(crate) fn deserialize_encap(typ: u16, payload: &[u8]) -> Result<LwtEncap> {
+    let typ = LwtEncapType::from(typ);
+    let encap = match typ {
+        LwtEncapType::Mpls => LwtEncap::Mpls(deserialize_mpls(payload)?),
+        LwtEncapType::Ip => LwtEncap::Ip(deserialize_ip_tunnel(payload)?),
+        LwtEncapType::Ip6 => LwtEncap::Ip6(deserialize_ip_tunnel(payload)?),
+        LwtEncapType::Seg6 => LwtEncap::Seg6(deserialize_seg6(payload)?),
+        LwtEncapType::Seg6Local => LwtEncap::Seg6Local(deserialize_seg6_local(payload)?),
+        LwtEncapType::Bpf => LwtEncap::Bpf(deserialize_bpf(payload)?),
+        typ => LwtEncap::Other {
+            typ,
+            data: payload.to_vec(),
+        },
+    };
+    Ok(encap)
+}
+
+/// Encode the attributes of `RTA_ENCAP`.
+pub(crate) fn serialize_encap(encap: &LwtEncap) -> Result<Vec<u8>> {
+    Ok(match encap {
+        LwtEncap::Mpls(mpls) => serialize_mpls(mpls),
+        LwtEncap::Ip(tunnel) | LwtEncap::Ip6(tunnel) => serialize_ip_tunnel(tunnel),
+        LwtEncap::Seg6(seg6) => serialize_seg6(seg6)?,
+        LwtEncap::Seg6Local(seg6) => serialize_seg6_local(seg6)?,
+        LwtEncap::Bpf(bpf) => serialize_bpf(bpf),
+        LwtEncap::Other { data, .. } => data.clone(),
+    })
+}
+
+fn read_attributes(payload: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    let mut reader = SliceReader::new(payload);
+    let mut attrs = vec![];
+    while !reader.is_empty() {
+        attrs.push(reader.read_attribute()?);
+    }
+    Ok(attrs)
+}
+
+fn deserialize_mpls(payload: &[u8]) -> Result<MplsEncap> {
+    let mut mpls = MplsEncap::default();
+    for (typ, value) in read_attributes(payload)? {
+        match typ {
+            MPLS_IPTUNNEL_DST => mpls.labels = deserialize_labels(value)?,
+            MPLS_IPTUNNEL_TTL => mpls.ttl = Some(deserialize_u8(value)?),
+            _ => {}
+        }
+    }
+    Ok(mpls)
+}
+
+fn serialize_mpls(mpls: &MplsEncap) -> Vec<u8> {
+    let mut bytes = vec![];
+    push_attribute(
+        &mut bytes,
+        MPLS_IPTUNNEL_DST,
+        &serialize_labels(&mpls.labels),
+    );
+    if let Some(ttl) = mpls.ttl {
+        push_attribute(&mut bytes, MPLS_IPTUNNEL_TTL, &[ttl]);
+    }
+    bytes
+}
+
+// The IPv4 and IPv6 attributes only differ in the size of the addresses.
+// The ID and flags are in network byte order.
+fn deserialize_ip_tunnel(payload: &[u8]) -> Result<IpTunnelEncap> {
+    let mut tunnel = IpTunnelEncap::default();
+    for (typ, value) in read_attributes(payload)? {
+        match typ {
+            LWTUNNEL_IP_ID => tunnel.id = Some(deserialize_u64_be(value)?),
+            LWTUNNEL_IP_DST => tunnel.dest = Some(deserialize_ip_addr(value)?),
+            LWTUNNEL_IP_SRC => tunnel.source = Some(deserialize_ip_addr(value)?),
+            LWTUNNEL_IP_TTL => tunnel.ttl = Some(deserialize_u8(value)?),
+            LWTUNNEL_IP_TOS => tunnel.tos = Some(deserialize_u8(value)?),
+            LWTUNNEL_IP_FLAGS => tunnel.flags = Some(deserialize_u16_be(value)?),
+            _ => {}
+        }
+    }
+    Ok(tunnel)
+}
+
+fn serialize_ip_tunnel(tunnel: &IpTunnelEncap) -> Vec<u8> {
+    let mut bytes = vec![];
+    if let Some(id) = tunnel.id {
+        push_attribute(&mut bytes, LWTUNNEL_IP_ID, &id.to_be_bytes());
+    }
+    if let Some(addr) = &tunnel.dest {
+        push_attribute(&mut bytes, LWTUNNEL_IP_DST, &serialize_ip_addr(addr));
+    }
+    if let Some(addr) = &tunnel.source {
+        push_attribute(&mut bytes, LWTUNNEL_IP_SRC, &serialize_ip_addr(addr));
+    }
+    if let Some(ttl) = tunnel.ttl {
+        push_attribute(&mut bytes, LWTUNNEL_IP_TTL, &[ttl]);
+    }
+    if let Some(tos) = tunnel.tos {
+        push_attribute(&mut bytes, LWTUNNEL_IP_TOS, &[tos]);
+    }
+    if let Some(flags) = tunnel.flags {
+        push_attribute(&mut bytes, LWTUNNEL_IP_FLAGS, &flags.to_be_bytes());
+    }
+    bytes
+}
+
+// SEG6_IPTUNNEL_SRH holds the mode, followed by the segment routing header
+fn deserialize_seg6(payload: &[u8]) -> Result<Seg6Encap> {
+    let mut seg6 = Seg6Encap::default();
+    for (typ, value) in read_attributes(payload)? {
+        if typ == SEG6_IPTUNNEL_SRH {
+            let (mode, srh) = value
+                .split_first_chunk::<4>()
+                .ok_or(Error::ErrUnexpectedEof)?;
+            seg6.mode = i32::from_le_bytes(*mode).into();
+            seg6.segments = deserialize_srh(srh, seg6.mode == Seg6Mode::Inline)?;
+        }
+    }
+    Ok(seg6)
+}
+
+fn serialize_seg6(seg6: &Seg6Encap) -> Result<Vec<u8>> {
+    let mut value = i32::from(seg6.mode).to_le_bytes().to_vec();
+    value.append(&mut serialize_srh(
+        &seg6.segments,
+        seg6.mode == Seg6Mode::Inline,
+    )?);
+
+    let mut bytes = vec![];
+    push_attribute(&mut bytes, SEG6_IPTUNNEL_SRH, &value);
+    Ok(bytes)
+}
+
+fn deserialize_seg6_local(payload: &[u8]) -> Result<Seg6LocalEncap> {
+    let attrs = read_attributes(payload)?;
+    let mut seg6 = Seg6LocalEncap::default();
+
+    // The action decides how the header is laid out, and may come after it
+    for (typ, value) in &attrs {
+        if *typ == SEG6_LOCAL_ACTION {
+            seg6.action = deserialize_u32(value)?.into();
+        }
+    }
+
+    for (typ, value) in attrs {
+        match typ {
+            SEG6_LOCAL_SRH => {
+                let inline = seg6.action == Seg6LocalAction::EndB6;
+                seg6.segments = deserialize_srh(value, inline)?;
+            }
+            SEG6_LOCAL_TABLE => seg6.table = Some(deserialize_u32(value)?),
+            SEG6_LOCAL_VRFTABLE => seg6.vrf_table = Some(deserialize_u32(value)?),
+            SEG6_LOCAL_NH4 => {
+                let IpAddr::V4(addr) = deserialize_ip_addr(value)? else {
+                    return Err(Error::ErrUnexpectedEof);
+                };
+                seg6.nh4 = Some(addr);
+            }
+            SEG6_LOCAL_NH6 => {
+                let IpAddr::V6(addr) = deserialize_ip_addr(value)? else {
+                    return Err(Error::ErrUnexpectedEof);
+                };
+                seg6.nh6 = Some(addr);
+            }
+            SEG6_LOCAL_IIF => seg6.input_interface_index = Some(deserialize_index(value)?),
+            SEG6_LOCAL_OIF => seg6.output_interface_index = Some(deserialize_index(value)?),
+            SEG6_LOCAL_BPF => seg6.bpf = Some(deserialize_bpf_program(value)?),
+            _ => {}
+        }
+    }
+
+    Ok(seg6)
+}
+
+fn serialize_seg6_local(seg6: &Seg6LocalEncap) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    let action = u32::from(seg6.action).to_le_bytes();
+    push_attribute(&mut bytes, SEG6_LOCAL_ACTION, &action);
+
+    if !seg6.segments.is_empty() {
+        let inline = seg6.action == Seg6LocalAction::EndB6;
+        push_attribute(
+            &mut bytes,
+            SEG6_LOCAL_SRH,
+            &serialize_srh(&seg6.segments, inline)?,
+        );
+    }
+    if let Some(table) = seg6.table {
+        push_attribute(&mut bytes, SEG6_LOCAL_TABLE, &table.to_le_bytes());
+    }
+    if let Some(table) = seg6.vrf_table {
+        push_attribute(&mut bytes, SEG6_LOCAL_VRFTABLE, &table.to_le_bytes());
+    }
+    if let Some(addr) = seg6.nh4 {
+        push_attribute(&mut bytes, SEG6_LOCAL_NH4, &addr.octets());
+    }
+    if let Some(addr) = seg6.nh6 {
+        push_attribute(&mut bytes, SEG6_LOCAL_NH6, &addr.octets());
+    }
+    if let Some(index) = seg6.input_interface_index {
+        push_attribute(&mut bytes, SEG6_LOCAL_IIF, &index.to_le_bytes());
+    }
+    if let Some(index) = seg6.output_interface_index {
+        push_attribute(&mut bytes, SEG6_LOCAL_OIF, &index.to_le_bytes());
+    }
+    if let Some(prog) = &seg6.bpf {
+        push_nested_attribute(&mut bytes, SEG6_LOCAL_BPF, &serialize_bpf_program(prog));
+    }
+
+    Ok(bytes)
+}
+
+fn deserialize_index(value: &[u8]) -> Result<i32> {
+    i32::try_from(deserialize_u32(value)?).map_err(|_| Error::ErrValueConversion)
+}
+
+fn deserialize_bpf(payload: &[u8]) -> Result<BpfEncap> {
+    let mut bpf = BpfEncap::default();
+    for (typ, value) in read_attributes(payload)? {
+        match typ {
+            LWT_BPF_IN => bpf.input = Some(deserialize_bpf_program(value)?),
+            LWT_BPF_OUT => bpf.output = Some(deserialize_bpf_program(value)?),
+            LWT_BPF_XMIT => bpf.xmit = Some(deserialize_bpf_program(value)?),
+            LWT_BPF_XMIT_HEADROOM => bpf.xmit_headroom = Some(deserialize_u32(value)?),
+            _ => {}
+        }
+    }
+    Ok(bpf)
+}
+
+fn serialize_bpf(bpf: &BpfEncap) -> Vec<u8> {
+    let mut bytes = vec![];
+    let progs = [
+        (LWT_BPF_IN, &bpf.input),
+        (LWT_BPF_OUT, &bpf.output),
+        (LWT_BPF_XMIT, &bpf.xmit),
+    ];
+    for (typ, prog) in progs {
+        if let Some(prog) = prog {
+            push_nested_attribute(&mut bytes, typ, &serialize_bpf_program(prog));
+        }
+    }
+    if let Some(headroom) = bpf.xmit_headroom {
+        push_attribute(&mut bytes, LWT_BPF_XMIT_HEADROOM, &headroom.to_le_bytes());
+    }
+    bytes
+}
+
+fn deserialize_bpf_program(payload: &[u8]) -> Result<BpfProgram> {
+    let mut prog = BpfProgram::default();
+    for (typ, value) in read_attributes(payload)? {
+        match typ {
+            LWT_BPF_PROG_FD => {
+                let fd = deserialize_u32(value)?;
+                prog.fd = Some(RawFd::try_from(fd).map_err(|_| Error::ErrValueConversion)?);
+            }
+            LWT_BPF_PROG_NAME => prog.name = deserialize_ascii(value),
+            _ => {}
+        }
+    }
+    Ok(prog)
+}
+
+fn serialize_bpf_program(prog: &BpfProgram) -> Vec<u8> {
+    let mut bytes = vec![];
+    if let Some(fd) = prog.fd {
+        push_attribute(&mut bytes, LWT_BPF_PROG_FD, &fd.to_le_bytes());
+    }
+    push_attribute(&mut bytes, LWT_BPF_PROG_NAME, &serialize_ascii(&prog.name));
+    bytes
+}
+
+// The segment routing header lists the segments in reverse, from the last
+// one to the first. With inline insertion, the last segment is reserved for
+// the original destination of the packet, which the kernel fills in. See
+// `struct ipv6_sr_hdr` in
+// https://github.com/torvalds/linux/blob/master/include/uapi/linux/seg6.h
+const SRH_LEN: usize = 8;
+const SRH_TYPE: u8 = 4;
+
+fn deserialize_srh(srh: &[u8], inline: bool) -> Result<Vec<Ipv6Addr>> {
+    let hdr = srh.get(..SRH_LEN).ok_or(Error::ErrUnexpectedEof)?;
+    let count = usize::from(hdr[4]) + 1;
+
+    let mut segments = srh[SRH_LEN..]
+        .chunks_exact(16)
+        .take(count)
+        .map(|segment| <[u8; 16]>::try_from(segment).map(Ipv6Addr::from))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::ErrUnexpectedEof)?;
+    if segments.len() != count {
+        return Err(Error::ErrUnexpectedEof);
+    }
+
+    if inline {
+        segments.remove(0);
+    }
+    segments.reverse();
+    Ok(segments)
+}
+
+fn serialize_srh(segments: &[Ipv6Addr], inline: bool) -> Result<Vec<u8>> {
+    let mut reversed = segments.iter().rev().copied().collect::<Vec<_>>();
+    if inline {
+        reversed.insert(0, Ipv6Addr::UNSPECIFIED);
+    }
+
+    // The length is in units of 8 bytes, not counting the first 8, so a u8
+    // holds at most 127 segments
+    let too_many = || Error::ErrTooManySegments(segments.len());
+    let count = u8::try_from(reversed.len()).map_err(|_| too_many())?;
+    let hdrlen = count.checked_mul(2).ok_or_else(too_many)?;
+    let last = count.saturating_sub(1);
+
+    let mut bytes = vec![0, hdrlen, SRH_TYPE, last, last, 0, 0, 0];
+    for segment in reversed {
+        bytes.extend_from_slice(&segment.octets());
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RTA_ENCAP of `encap seg6 mode encap segs
+    // 2001:db8:a::1,2001:db8:b::2,2001:db8:c::3`, as dumped by the kernel
+    #[rustfmt::skip]
+    const KERNEL_SEG6: &[u8] = &[
+        0x40, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x06, 0x04, 0x02, 0x02, 0x00, 0x00, 0x00,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0c, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0b, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    ];
+
+    // RTA_ENCAP of `encap seg6 mode inline segs 2001:db8:a::1,2001:db8:b::2`,
+    // whose first entry is reserved for the destination of the packet
+    #[rustfmt::skip]
+    const KERNEL_SEG6_INLINE: &[u8] = &[
+        0x40, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x06, 0x04, 0x02, 0x02, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0b, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    ];
+
+    // RTA_ENCAP of `encap seg6local action End.X nh6 2001:db8:1::1`
+    #[rustfmt::skip]
+    const KERNEL_SEG6LOCAL_END_X: &[u8] = &[
+        0x08, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x05, 0x00, 0x20, 0x01, 0x0d, 0xb8,
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01,
+    ];
+
+    // RTA_ENCAP of `encap seg6local action End.DT6 table 100`
+    #[rustfmt::skip]
+    const KERNEL_SEG6LOCAL_END_DT6: &[u8] = &[
+        0x08, 0x00, 0x01, 0x00, 0x07, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x03, 0x00, 0x64, 0x00, 0x00, 0x00,
+    ];
+
+    // RTA_ENCAP of `encap seg6local action End.B6 srh segs
+    // 2001:db8:a::1,2001:db8:b::2`, which inserts the header like inline mode
+    #[rustfmt::skip]
+    const KERNEL_SEG6LOCAL_END_B6: &[u8] = &[
+        0x08, 0x00, 0x01, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x3c, 0x00, 0x02, 0x00, 0x00, 0x06, 0x04, 0x02,
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x20, 0x01, 0x0d, 0xb8,
+        0x00, 0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x02, 0x20, 0x01, 0x0d, 0xb8,
+        0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01,
+    ];
+
+    // 2001:db8:a::1, 2001:db8:b::2 and so on
+    fn segment(n: u16) -> Ipv6Addr {
+        Ipv6Addr::new(0x2001, 0xdb8, n, 0, 0, 0, 0, n - 9)
+    }
+
+    // Decodes the kernel's bytes, and encodes them back the same way
+    fn assert_kernel_encap(bytes: &[u8], encap: &LwtEncap) -> Result<()> {
+        assert_eq!(&deserialize_encap(encap.typ().into(), bytes)?, encap);
+        assert_eq!(serialize_encap(encap)?, bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn srh_segments_are_in_visiting_order() -> Result<()> {
+        let encap = Seg6Encap::builder()
+            .mode(Seg6Mode::Encap)
+            .segments(vec![segment(0xa), segment(0xb), segment(0xc)])
+            .build()?;
+        assert_kernel_encap(KERNEL_SEG6, &LwtEncap::Seg6(encap))
+    }
+
+    #[test]
+    fn inline_srh_leaves_out_the_destination() -> Result<()> {
+        let encap = Seg6Encap::builder()
+            .mode(Seg6Mode::Inline)
+            .segments(vec![segment(0xa), segment(0xb)])
+            .build()?;
+        assert_kernel_encap(KERNEL_SEG6_INLINE, &LwtEncap::Seg6(encap))
+    }
+
+    #[test]
+    fn kernel_seg6_local_is_decoded() -> Result<()> {
+        let end_x = Seg6LocalEncap::builder()
+            .action(Seg6LocalAction::EndX)
+            .nh6(Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 1))
+            .build()?;
+        assert_kernel_encap(KERNEL_SEG6LOCAL_END_X, &LwtEncap::Seg6Local(end_x))?;
+
+        let end_dt6 = Seg6LocalEncap::builder()
+            .action(Seg6LocalAction::EndDt6)
+            .table(100u32)
+            .build()?;
+        assert_kernel_encap(KERNEL_SEG6LOCAL_END_DT6, &LwtEncap::Seg6Local(end_dt6))?;
+
+        let end_b6 = Seg6LocalEncap::builder()
+            .action(Seg6LocalAction::EndB6)
+            .segments(vec![segment(0xa), segment(0xb)])
+            .build()?;
+        assert_kernel_encap(KERNEL_SEG6LOCAL_END_B6, &LwtEncap::Seg6Local(end_b6))
+    }
+
+    #[test]
+    fn oversized_srh_is_rejected() -> Result<()> {
+        let segments = (0..128).map(|n| segment(n + 10)).collect::<Vec<_>>();
+
+        // 127 segments are the most that fit, or 126 with the destination
+        let full = Seg6Encap::builder()
+            .segments(segments[..127].to_vec())
+            .build()?;
+        let full = LwtEncap::Seg6(full);
+        assert_eq!(round_trip(&full)?, full);
+
+        let inline = Seg6Encap::builder()
+            .mode(Seg6Mode::Inline)
+            .segments(segments[..127].to_vec())
+            .build()?;
+        assert!(matches!(
+            serialize_encap(&LwtEncap::Seg6(inline)),
+            Err(Error::ErrTooManySegments(127))
+        ));
+
+        let end_b6 = Seg6LocalEncap::builder()
+            .action(Seg6LocalAction::EndB6Encaps)
+            .segments(segments)
+            .build()?;
+        assert!(matches!(
+            serialize_encap(&LwtEncap::Seg6Local(end_b6)),
+            Err(Error::ErrTooManySegments(128))
+        ));
+        Ok(())
+    }
+
+    fn round_trip(encap: &LwtEncap) -> Result<LwtEncap> {
+        deserialize_encap(encap.typ().into(), &serialize_encap(encap)?)
+    }
+
+    #[test]
+    fn seg6_local_round_trips() -> Result<()> {
+        let encap = LwtEncap::Seg6Local(
+            Seg6LocalEncap::builder()
+                .action(Seg6LocalAction::EndB6Encaps)
+                .segments(vec![segment(0xa), segment(0xb), segment(0xc)])
+                .vrf_table(10u32)
+                .nh4(Ipv4Addr::new(192, 0, 2, 1))
+                .input_interface_index(2)
+                .output_interface_index(3)
+                .bpf(BpfProgram::new(5, "end_bpf"))
+                .build()?,
+        );
+        assert_eq!(round_trip(&encap)?, encap);
+        Ok(())
+    }
+
+    #[test]
+    fn mpls_round_trips() -> Result<()> {
+        let encap = LwtEncap::Mpls(
+            MplsEncap::builder()
+                .labels(vec![MplsLabel::new(100)?, MplsLabel::new(200)?])
+                .ttl(8)
+                .build()?,
+        );
+        assert_eq!(round_trip(&encap)?, encap);
+        Ok(())
+    }
+
+    #[test]
+    fn bpf_round_trips() -> Result<()> {
+        let encap = LwtEncap::Bpf(
+            BpfEncap::builder()
+                .input(BpfProgram::new(3, "lwt_in"))
+                .output(BpfProgram::new(4, "lwt_out"))
+                .xmit(BpfProgram::new(5, "lwt_xmit"))
+                .xmit_headroom(14u32)
+                .build()?,
+        );
+        assert_eq!(round_trip(&encap)?, encap);
+        Ok(())
+    }
+
+    #[test]
+    fn ip_tunnel_round_trips() -> Result<()> {
+        let encap = LwtEncap::Ip6(
+            IpTunnelEncap::builder()
+                .id(42u64)
+                .dest(IpAddr::from(segment(0xa)))
+                .source(IpAddr::from(segment(0xb)))
+                .ttl(64)
+                .tos(0x10)
+                .flags(1u16)
+                .build()?,
+        );
+        assert_eq!(round_trip(&encap)?, encap);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::{header_table, Route, RouteAttrValue, RouteMessage, RouteProtocol, RouteTable};
+use crate::bytes::serialize_attribute;
+use crate::route::RTM_F_CLONED;
+use crate::Error;
+use crate::Result;
+use derive_builder::Builder;
+
+/// Selects the routes returned by [`crate::NetlinkStream::list_routes_filtered`].
+///
+/// With strict checking, the kernel applies the filter itself and marks the
+/// routes it sends with [`crate::Flags::DUMP_FILTERED`]. Kernels that can't are
+/// still filtered by the client, so the result is the same everywhere.
+///
+/// ```rust
+/// use netlink::route::route::{RouteFilter, RouteProtocol, RouteTable};
+///
+/// # fn main() -> netlink::Result<()> {
+/// // Routes in the main table that were installed by the kernel
+/// let filter = RouteFilter::builder()
+///     .table(RouteTable::Main)
+///     .protocol(RouteProtocol::Kernel)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct RouteFilter {
+    /// Only routes in this table
+    pub table: Option<RouteTable>,
+    /// Only routes installed by this protocol, like [`RouteProtocol::Kernel`]
+    pub protocol: Option<RouteProtocol>,
+    /// Only routes through the interface with this index
+    pub output_interface: Option<i32>,
+    /// List the cached exceptions, like PMTU exceptions, instead of the
+    /// configured routes, like `ip route show cache`
+    pub cloned: bool,
+}
+
+impl RouteFilter {
+    #[must_use]
+    pub fn builder() -> RouteFilterBuilder {
+        RouteFilterBuilder::default()
+    }
+
+    /// Whether the route is selected by this filter.
+    #[must_use]
+    pub fn matches(&self, route: &Route) -> bool {
+        if self.table.is_some_and(|table| route.table != table) {
+            return false;
+        }
+        if self
+            .protocol
+            .is_some_and(|protocol| route.protocol != protocol)
+        {
+            return false;
+        }
+        if self.output_interface.is_some() && route.output_interface_index != self.output_interface
+        {
+            return false;
+        }
+        route.cloned == self.cloned
+    }
+
+    // Fill in the header fields and attributes that ask the kernel to apply
+    // the filter. Strict checking rejects any others.
+    pub(crate) fn apply(&self, rtmsg: &mut RouteMessage) -> Result<Vec<u8>> {
+        let mut attrs = vec![];
+
+        if let Some(table) = self.table {
+            rtmsg.table = header_table(table);
+            attrs.push(RouteAttrValue::Table(table.into()));
+        }
+        if let Some(protocol) = self.protocol {
+            rtmsg.protocol = protocol.into();
+        }
+        if let Some(index) = self.output_interface {
+            attrs.push(RouteAttrValue::OutputInterfaceIndex(index));
+        }
+        if self.cloned {
+            rtmsg.flags |= RTM_F_CLONED;
+        }
+
+        let mut bytes = vec![];
+        for attr in attrs {
+            let (typ, value) = attr.serialize()?;
+            bytes.append(&mut serialize_attribute(typ.into(), &value)?);
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_id_matches_named_table() -> Result<()> {
+        let filter = RouteFilter::builder().table(RouteTable::Id(254)).build()?;
+        let route = Route {
+            table: RouteTable::Main,
+            ..Default::default()
+        };
+        assert!(filter.matches(&route));
+
+        let route = Route {
+            table: RouteTable::Id(100),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&route));
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use serde::Serialize;
+use std::fmt;
+
+// Conversions to and from the raw value, which keep unknown values in the
+// catch-all variant so that nothing is lost, and the names `ip route` uses.
+//
+// The catch-all variant can also be built with a value that has a name, like
+// `RouteTable::Id(254)`, so values are compared and hashed by their raw value
+// to make it equal to the named variant, `RouteTable::Main`.
+macro_rules! impl_raw_enum {
+    ($name:ident, $raw:ty, $other:ident, { $($variant:ident = $value:literal => $text:literal,)* }) => {
+        impl From<$raw> for $name {
+            fn from(value: $raw) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    other => Self::$other(other),
+                }
+            }
+        }
+
+        impl From<$name> for $raw {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::$other(other) => other,
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                <$raw>::from(*self) == <$raw>::from(*other)
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl std::hash::Hash for $name {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                <$raw>::from(*self).hash(state);
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match Self::from(<$raw>::from(*self)) {
+                    $(Self::$variant => f.write_str($text),)*
+                    Self::$other(other) => write!(f, "{other}"),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_raw_enum;
+
+/// Routing table of a route. Tables other than the reserved ones are
+/// identified by a number, like `ip route add ... table 100`.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum RouteTable {
+    /// No table, which the kernel treats as [`RouteTable::Main`] when adding
+    /// a route
+    #[default]
+    Unspec,
+    /// Stands in for tables above 255 in the header of a route, which are
+    /// carried by `RTA_TABLE` instead
+    Compat,
+    Default,
+    Main,
+    /// Local and broadcast addresses
+    Local,
+    Id(u32),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(RouteTable, u32, Id, {
+    Unspec = 0 => "unspec",
+    Compat = 252 => "compat",
+    Default = 253 => "default",
+    Main = 254 => "main",
+    Local = 255 => "local",
+});
+
+/// What installed a route, like `ip route ... proto static`.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum RouteProtocol {
+    #[default]
+    Unspec,
+    /// Installed by an ICMP redirect
+    Redirect,
+    /// Installed by the kernel, like the routes of an interface's addresses
+    Kernel,
+    /// Installed when the system booted, which is the default of `ip route`
+    Boot,
+    /// Installed by the administrator to override dynamic routing
+    Static,
+    Gated,
+    /// Learned from an IPv6 router advertisement
+    Ra,
+    Mrt,
+    Zebra,
+    Bird,
+    DnRouted,
+    Xorp,
+    Ntk,
+    Dhcp,
+    Mrouted,
+    Keepalived,
+    Babel,
+    Openr,
+    Bgp,
+    Isis,
+    Ospf,
+    Rip,
+    Eigrp,
+    Other(u8),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(RouteProtocol, u8, Other, {
+    Unspec = 0 => "unspec",
+    Redirect = 1 => "redirect",
+    Kernel = 2 => "kernel",
+    Boot = 3 => "boot",
+    Static = 4 => "static",
+    Gated = 8 => "gated",
+    Ra = 9 => "ra",
+    Mrt = 10 => "mrt",
+    Zebra = 11 => "zebra",
+    Bird = 12 => "bird",
+    DnRouted = 13 => "dnrouted",
+    Xorp = 14 => "xorp",
+    Ntk = 15 => "ntk",
+    Dhcp = 16 => "dhcp",
+    Mrouted = 17 => "mrouted",
+    Keepalived = 18 => "keepalived",
+    Babel = 42 => "babel",
+    Openr = 99 => "openr",
+    Bgp = 186 => "bgp",
+    Isis = 187 => "isis",
+    Ospf = 188 => "ospf",
+    Rip = 189 => "rip",
+    Eigrp = 192 => "eigrp",
+});
+
+/// How far the destination of a route is, like `ip route ... scope link`.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum RouteScope {
+    /// Reachable through a gateway
+    #[default]
+    Universe,
+    Site,
+    /// On the attached link
+    Link,
+    /// A local address
+    Host,
+    /// No destination, which matches any scope when deleting a route
+    Nowhere,
+    Other(u8),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(RouteScope, u8, Other, {
+    Universe = 0 => "global",
+    Site = 200 => "site",
+    Link = 253 => "link",
+    Host = 254 => "host",
+    Nowhere = 255 => "nowhere",
+});
+
+/// What happens to packets that match a route, like
+/// `ip route add blackhole ...`.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum RouteType {
+    #[default]
+    Unspec,
+    /// Forwarded to a gateway or a directly attached destination
+    Unicast,
+    /// Delivered locally
+    Local,
+    /// Delivered locally, and sent as a broadcast
+    Broadcast,
+    /// Delivered locally, and sent as a unicast
+    Anycast,
+    Multicast,
+    /// Dropped silently
+    Blackhole,
+    /// Dropped with an ICMP unreachable error
+    Unreachable,
+    /// Dropped with an ICMP prohibited error
+    Prohibit,
+    /// Continues the lookup in the next table of the routing policy
+    Throw,
+    Nat,
+    Xresolve,
+    Other(u8),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(RouteType, u8, Other, {
+    Unspec = 0 => "unspec",
+    Unicast = 1 => "unicast",
+    Local = 2 => "local",
+    Broadcast = 3 => "broadcast",
+    Anycast = 4 => "anycast",
+    Multicast = 5 => "multicast",
+    Blackhole = 6 => "blackhole",
+    Unreachable = 7 => "unreachable",
+    Prohibit = 8 => "prohibit",
+    Throw = 9 => "throw",
+    Nat = 10 => "nat",
+    Xresolve = 11 => "xresolve",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::route::{LwtEncapType, Seg6LocalAction, Seg6Mode};
+    use crate::route::rule::RuleAction;
+    use std::collections::HashSet;
+
+    // Every raw value converts back to itself, and the catch-all variant
+    // built with a named value is the same as the named variant
+    fn assert_round_trips<T, R>(values: impl IntoIterator<Item = R>)
+    where
+        T: From<R> + Into<R> + Copy + Eq + std::hash::Hash + fmt::Debug + fmt::Display,
+        R: Copy + PartialEq + fmt::Debug,
+    {
+        for raw in values {
+            let value = T::from(raw);
+            assert_eq!(value.into(), raw);
+            assert_eq!(HashSet::from([value]).len(), 1);
+        }
+    }
+
+    #[test]
+    fn u8_values_round_trip() {
+        assert_round_trips::<RouteProtocol, u8>(0..=u8::MAX);
+        assert_round_trips::<RouteScope, u8>(0..=u8::MAX);
+        assert_round_trips::<RouteType, u8>(0..=u8::MAX);
+        assert_round_trips::<RuleAction, u8>(0..=u8::MAX);
+    }
+
+    #[test]
+    fn wider_values_round_trip() {
+        assert_round_trips::<RouteTable, u32>((0..=1024).chain([u32::MAX]));
+        assert_round_trips::<LwtEncapType, u16>(0..=1024);
+        assert_round_trips::<Seg6Mode, i32>(-1..=1024);
+        assert_round_trips::<Seg6LocalAction, u32>(0..=1024);
+    }
+
+    #[test]
+    fn catch_all_equals_named_variant() {
+        assert_eq!(RouteTable::Id(254), RouteTable::Main);
+        assert_eq!(RouteTable::Main, RouteTable::Id(254));
+        assert_ne!(RouteTable::Id(100), RouteTable::Main);
+        assert_eq!(RouteProtocol::Other(4), RouteProtocol::Static);
+        assert_eq!(RouteScope::Other(253), RouteScope::Link);
+        assert_eq!(RouteType::Other(6), RouteType::Blackhole);
+
+        let tables = HashSet::from([RouteTable::Id(254), RouteTable::Main]);
+        assert_eq!(tables.len(), 1);
+    }
+
+    #[test]
+    fn catch_all_is_displayed_by_name() {
+        assert_eq!(RouteTable::Id(254).to_string(), "main");
+        assert_eq!(RouteTable::Id(100).to_string(), "100");
+        assert_eq!(RouteProtocol::Other(4).to_string(), "static");
+        assert_eq!(RouteProtocol::Other(200).to_string(), "200");
+    }
+}
@@ -0,0 +1,240 @@
+use super::{parse_route, serialize_rtmsg, Route, RouteAttrValue, RouteMessage, RouteMessageType};
+use crate::route::{AddressFamily, IpPrefix, OperationError, RequestContext, RTM_F_FIB_MATCH};
+use crate::transport::Transport;
+use crate::Result;
+use crate::{Error, Flags, GetFlags, MessageFlags, NetlinkMessage, NetlinkRequest, NetlinkStream};
+use derive_builder::Builder;
+use nix::errno::Errno;
+use std::net::IpAddr;
+
+/// Describes the packet whose route is looked up by
+/// [`NetlinkStream::get_route`], like the arguments of `ip route get`.
+///
+/// ```rust
+/// use netlink::route::route::GetRouteOptions;
+/// use std::net::Ipv4Addr;
+///
+/// # fn main() -> netlink::Result<()> {
+/// // ip route get 8.8.8.8 from 192.168.1.10 ipproto tcp dport 443
+/// let options = GetRouteOptions::builder()
+///     .source(Ipv4Addr::new(192, 168, 1, 10))
+///     .ip_proto(6u8)
+///     .dest_port(443u16)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct GetRouteOptions {
+    /// Source address of the packet
+    pub source: Option<IpAddr>,
+    /// Index of the interface the packet arrives on, which makes this a
+    /// lookup for forwarded traffic
+    pub input_interface: Option<i32>,
+    /// Index of the interface the packet must leave through
+    pub output_interface: Option<i32>,
+    /// Firewall mark of the packet, for policy routing
+    pub mark: Option<u32>,
+    /// User that sends the packet, for policy routing
+    pub uid: Option<u32>,
+    /// IP protocol of the packet, like 6 for TCP
+    pub ip_proto: Option<u8>,
+    pub source_port: Option<u16>,
+    pub dest_port: Option<u16>,
+    /// Return the route as it's installed in the table, like
+    /// `ip route get fibmatch`, instead of the route resolved for the
+    /// destination
+    pub fib_match: bool,
+}
+
+impl GetRouteOptions {
+    #[must_use]
+    pub fn builder() -> GetRouteOptionsBuilder {
+        GetRouteOptionsBuilder::default()
+    }
+}
+
+impl<T: Transport> NetlinkStream<T> {
+    /// Look up the route the kernel would select for a destination, like
+    /// `ip route get`. The returned route includes the output interface and
+    /// the preferred source address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure, like
+    /// [`OperationErrorKind::NetworkUnreachable`] if there's no route to the
+    /// destination, and [`Error::ErrFamilyMismatch`] if the source address
+    /// isn't of the same family as the destination.
+    ///
+    /// [`OperationErrorKind::NetworkUnreachable`]: crate::route::OperationErrorKind::NetworkUnreachable
+    pub fn get_route(&mut self, dest: IpAddr, options: &GetRouteOptions) -> Result<Route> {
+        let req = LookupRoute {
+            dest,
+            options: options.clone(),
+        };
+
+        // The kernel answers with a single route
+        self.execute(&req)?
+            .into_iter()
+            .next()
+            .ok_or(Error::ErrNoResponse("route"))
+    }
+}
+
+/// Look up the route to a destination. See [`NetlinkStream::get_route`].
+#[derive(Clone, Debug)]
+pub struct LookupRoute {
+    pub dest: IpAddr,
+    pub options: GetRouteOptions,
+}
+
+impl NetlinkRequest for LookupRoute {
+    type Flags = MessageFlags<GetFlags>;
+    type Response = Route;
+
+    fn message_type(&self) -> u16 {
+        RouteMessageType::GetRoute.into()
+    }
+
+    fn flags(&self) -> Self::Flags {
+        MessageFlags::new(Flags::REQUEST, GetFlags::empty())
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        let options = &self.options;
+        let family = AddressFamily::of(&self.dest);
+        let host_len = IpPrefix::from(self.dest).prefix_len();
+
+        // The kernel reads the source as an address of the route's family
+        if let Some(source) = options.source {
+            if AddressFamily::of(&source) != family {
+                return Err(Error::ErrFamilyMismatch(self.dest, source));
+            }
+        }
+
+        // Strict checking only accepts host prefixes, and no table, protocol,
+        // scope or type
+        let rtmsg = RouteMessage::builder()
+            .family(family.into())
+            .dst_len(host_len)
+            .src_len(options.source.map_or(0, |_| host_len))
+            .flags(if options.fib_match {
+                RTM_F_FIB_MATCH
+            } else {
+                0
+            })
+            .build()?;
+
+        let mut attrs = vec![RouteAttrValue::Dest(self.dest)];
+        if let Some(addr) = options.source {
+            attrs.push(RouteAttrValue::Source(addr));
+        }
+        if let Some(index) = options.input_interface {
+            attrs.push(RouteAttrValue::InputInterfaceIndex(index));
+        }
+        if let Some(index) = options.output_interface {
+            attrs.push(RouteAttrValue::OutputInterfaceIndex(index));
+        }
+        if let Some(mark) = options.mark {
+            attrs.push(RouteAttrValue::Mark(mark));
+        }
+        if let Some(uid) = options.uid {
+            attrs.push(RouteAttrValue::Uid(uid));
+        }
+        if let Some(proto) = options.ip_proto {
+            attrs.push(RouteAttrValue::IpProto(proto));
+        }
+        if let Some(port) = options.source_port {
+            attrs.push(RouteAttrValue::SourcePort(port));
+        }
+        if let Some(port) = options.dest_port {
+            attrs.push(RouteAttrValue::DestPort(port));
+        }
+
+        serialize_rtmsg(rtmsg, &attrs)
+    }
+
+    fn deserialize(&self, msg: &NetlinkMessage) -> Result<Route> {
+        parse_route(&msg.payload)
+    }
+
+    fn map_error(&self, errno: Errno) -> Error {
+        let dest = IpPrefix::from(self.dest);
+        let mut context =
+            RequestContext::new(RouteMessageType::GetRoute).prefix(dest.addr(), dest.prefix_len());
+        if let Some(index) = self.options.output_interface {
+            context = context.interface(index);
+        }
+        OperationError::new(context, errno).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::serialize_message;
+    use crate::MessageType;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    // Answers every request with an empty NLMSG_DONE
+    #[derive(Default)]
+    struct Silent {
+        replies: Vec<Vec<u8>>,
+    }
+
+    impl Transport for Silent {
+        fn pid(&self) -> u32 {
+            0
+        }
+
+        fn send(&mut self, buf: &[u8]) -> Result<()> {
+            let seq = u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]);
+            let done = serialize_message(MessageType::Done.into(), 0, seq, 0, vec![0; 4])?;
+            self.replies.push(done);
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>> {
+            self.replies
+                .pop()
+                .ok_or(Error::ErrRecvSocket(Errno::EAGAIN))
+        }
+    }
+
+    #[test]
+    fn empty_response_is_reported() {
+        let mut conn = NetlinkStream::new(Silent::default());
+        let dest = Ipv4Addr::new(192, 0, 2, 1).into();
+        let err = conn
+            .get_route(dest, &GetRouteOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, Error::ErrNoResponse("route")));
+    }
+
+    #[test]
+    fn source_of_another_family_is_rejected() -> Result<()> {
+        let mut conn = NetlinkStream::new(Silent::default());
+        let dest = IpAddr::from(Ipv4Addr::new(192, 0, 2, 1));
+        let source = IpAddr::from(Ipv6Addr::LOCALHOST);
+        let options = GetRouteOptions::builder().source(source).build()?;
+
+        let err = conn.get_route(dest, &options).unwrap_err();
+        assert!(matches!(err, Error::ErrFamilyMismatch(..)));
+        assert!(conn.get_ref().replies.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn source_is_a_host_prefix() -> Result<()> {
+        let req = LookupRoute {
+            dest: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).into(),
+            options: GetRouteOptions::builder()
+                .source(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2))
+                .build()?,
+        };
+        let route = parse_route(&req.serialize()?)?;
+        assert_eq!(route.source.map(|prefix| prefix.prefix_len()), Some(128));
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use crate::bytes::SliceReader;
+use crate::bytes::{deserialize_ascii, deserialize_u32, push_attribute, serialize_ascii};
+use crate::{Error, Result};
+use bitflags::bitflags;
+use derive_builder::Builder;
+use serde::{Serialize, Serializer};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// Type of the attributes nested in `RTA_METRICS`. See `RTAX_*` in
+/// [`rtnetlink.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/rtnetlink.h).
+#[repr(u16)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize_repr, Deserialize_repr)]
+pub enum RouteMetricType {
+    Unspec = 0,
+    Lock = 1,
+    Mtu = 2,
+    Window = 3,
+    Rtt = 4,
+    RttVar = 5,
+    Ssthresh = 6,
+    Cwnd = 7,
+    Advmss = 8,
+    Reordering = 9,
+    Hoplimit = 10,
+    Initcwnd = 11,
+    Features = 12,
+    RtoMin = 13,
+    Initrwnd = 14,
+    Quickack = 15,
+    CongestionControl = 16,
+    FastopenNoCookie = 17,
+}
+
+impl From<RouteMetricType> for u16 {
+    fn from(typ: RouteMetricType) -> Self {
+        typ as u16
+    }
+}
+
+bitflags! {
+    /// Metrics that are locked, so that the kernel doesn't update them from
+    /// what it learns, like `mtu lock 1400` in `ip route`.
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct RouteMetricsLock: u32 {
+        const MTU = 1 << RouteMetricType::Mtu as u32;
+        const WINDOW = 1 << RouteMetricType::Window as u32;
+        const RTT = 1 << RouteMetricType::Rtt as u32;
+        const RTTVAR = 1 << RouteMetricType::RttVar as u32;
+        const SSTHRESH = 1 << RouteMetricType::Ssthresh as u32;
+        const CWND = 1 << RouteMetricType::Cwnd as u32;
+        const ADVMSS = 1 << RouteMetricType::Advmss as u32;
+        const REORDERING = 1 << RouteMetricType::Reordering as u32;
+        const HOPLIMIT = 1 << RouteMetricType::Hoplimit as u32;
+        const INITCWND = 1 << RouteMetricType::Initcwnd as u32;
+        const RTO_MIN = 1 << RouteMetricType::RtoMin as u32;
+        const INITRWND = 1 << RouteMetricType::Initrwnd as u32;
+        const CONGESTION_CONTROL = 1 << RouteMetricType::CongestionControl as u32;
+    }
+}
+
+// Serialized as the raw bits, like the other flags fields of a route
+impl Serialize for RouteMetricsLock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+/// Per-route TCP and path parameters from `RTA_METRICS`. Values are in the
+/// kernel's units, like `ip route` prints them with `-raw`: times in
+/// milliseconds, except for `rtt` and `rttvar` which are scaled by 8 and 4.
+///
+/// ```rust
+/// use netlink::route::route::{Route, RouteMetrics, RouteMetricsLock};
+/// use netlink::route::IpPrefix;
+///
+/// # fn main() -> netlink::Result<()> {
+/// // ip route add 10.0.0.0/8 dev eth0 mtu lock 1400 initcwnd 10
+/// let metrics = RouteMetrics::builder()
+///     .mtu(1400u32)
+///     .initcwnd(10u32)
+///     .lock(RouteMetricsLock::MTU)
+///     .build()?;
+///
+/// let route = Route::builder()
+///     .dest("10.0.0.0/8".parse::<IpPrefix>()?)
+///     .output_interface_index(2)
+///     .metrics(metrics)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct RouteMetrics {
+    pub lock: RouteMetricsLock,
+    pub mtu: Option<u32>,
+    pub window: Option<u32>,
+    pub rtt: Option<u32>,
+    pub rttvar: Option<u32>,
+    pub ssthresh: Option<u32>,
+    pub cwnd: Option<u32>,
+    pub advmss: Option<u32>,
+    pub reordering: Option<u32>,
+    pub hoplimit: Option<u32>,
+    pub initcwnd: Option<u32>,
+    /// `RTAX_FEATURE_*` flags, like ECN
+    pub features: Option<u32>,
+    pub rto_min: Option<u32>,
+    pub initrwnd: Option<u32>,
+    pub quickack: Option<u32>,
+    /// Name of the TCP congestion control algorithm, like `bbr`
+    pub congestion_control: Option<String>,
+    pub fastopen_no_cookie: Option<u32>,
+}
+
+impl RouteMetrics {
+    #[must_use]
+    pub fn builder() -> RouteMetricsBuilder {
+        RouteMetricsBuilder::default()
+    }
+
+    /// Whether none of the metrics are set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn values(&self) -> [(RouteMetricType, Option<u32>); 15] {
+        #[rustfmt::skip]
+        let values = [
+            (RouteMetricType::Mtu, self.mtu),
+            (RouteMetricType::Window, self.window),
+            (RouteMetricType::Rtt, self.rtt),
+            (RouteMetricType::RttVar, self.rttvar),
+            (RouteMetricType::Ssthresh, self.ssthresh),
+            (RouteMetricType::Cwnd, self.cwnd),
+            (RouteMetricType::Advmss, self.advmss),
+            (RouteMetricType::Reordering, self.reordering),
+            (RouteMetricType::Hoplimit, self.hoplimit),
+            (RouteMetricType::Initcwnd, self.initcwnd),
+            (RouteMetricType::Features, self.features),
+            (RouteMetricType::RtoMin, self.rto_min),
+            (RouteMetricType::Initrwnd, self.initrwnd),
+            (RouteMetricType::Quickack, self.quickack),
+            (RouteMetricType::FastopenNoCookie, self.fastopen_no_cookie),
+        ];
+        values
+    }
+
+    fn value_mut(&mut self, typ: u16) -> Option<&mut Option<u32>> {
+        #[rustfmt::skip]
+        let value = match typ {
+            typ if typ == RouteMetricType::Mtu.into() => &mut self.mtu,
+            typ if typ == RouteMetricType::Window.into() => &mut self.window,
+            typ if typ == RouteMetricType::Rtt.into() => &mut self.rtt,
+            typ if typ == RouteMetricType::RttVar.into() => &mut self.rttvar,
+            typ if typ == RouteMetricType::Ssthresh.into() => &mut self.ssthresh,
+            typ if typ == RouteMetricType::Cwnd.into() => &mut self.cwnd,
+            typ if typ == RouteMetricType::Advmss.into() => &mut self.advmss,
+            typ if typ == RouteMetricType::Reordering.into() => &mut self.reordering,
+            typ if typ == RouteMetricType::Hoplimit.into() => &mut self.hoplimit,
+            typ if typ == RouteMetricType::Initcwnd.into() => &mut self.initcwnd,
+            typ if typ == RouteMetricType::Features.into() => &mut self.features,
+            typ if typ == RouteMetricType::RtoMin.into() => &mut self.rto_min,
+            typ if typ == RouteMetricType::Initrwnd.into() => &mut self.initrwnd,
+            typ if typ == RouteMetricType::Quickack.into() => &mut self.quickack,
+            typ if typ == RouteMetricType::FastopenNoCookie.into() => &mut self.fastopen_no_cookie,
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+// The payload of RTA_METRICS is a set of RTAX_* attributes
+pub(crate) fn deserialize_metrics(payload: &[u8]) -> Result<RouteMetrics> {
+    let mut reader = SliceReader::new(payload);
+    let mut metrics = RouteMetrics::default();
+
+    while !reader.is_empty() {
+        let (typ, value) = reader.read_attribute()?;
+        if typ == RouteMetricType::Lock.into() {
+            metrics.lock = RouteMetricsLock::from_bits_retain(deserialize_u32(value)?);
+        } else if typ == RouteMetricType::CongestionControl.into() {
+            metrics.congestion_control = Some(deserialize_ascii(value));
+        } else if let Some(metric) = metrics.value_mut(typ) {
+            *metric = Some(deserialize_u32(value)?);
+        }
+        // Skip metrics added by kernels newer than this library
+    }
+
+    Ok(metrics)
+}
+
+pub(crate) fn serialize_metrics(metrics: &RouteMetrics) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    if !metrics.lock.is_empty() {
+        let lock = metrics.lock.bits().to_le_bytes();
+        push_attribute(&mut bytes, RouteMetricType::Lock.into(), &lock);
+    }
+    for (typ, value) in metrics.values() {
+        if let Some(value) = value {
+            push_attribute(&mut bytes, typ.into(), &value.to_le_bytes());
+        }
+    }
+    if let Some(name) = &metrics.congestion_control {
+        let name = serialize_ascii(name);
+        push_attribute(&mut bytes, RouteMetricType::CongestionControl.into(), &name);
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::route::parse_route;
+
+    // RTM_NEWROUTE payload of `ip route add 203.0.113.0/24 dev nltest0 mtu
+    // lock 1400 initcwnd 10 rtt 100ms congctl lock cubic advmss 1360`, as
+    // dumped by the kernel
+    #[rustfmt::skip]
+    const KERNEL_ROUTE: &[u8] = &[
+        0x02, 0x18, 0x00, 0x00, 0xfe, 0x03, 0xfd, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0xfe, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00,
+        0xcb, 0x00, 0x71, 0x00, 0x38, 0x00, 0x08, 0x00,
+        0x08, 0x00, 0x01, 0x00, 0x04, 0x00, 0x01, 0x00,
+        0x08, 0x00, 0x02, 0x00, 0x78, 0x05, 0x00, 0x00,
+        0x08, 0x00, 0x04, 0x00, 0x20, 0x03, 0x00, 0x00,
+        0x08, 0x00, 0x08, 0x00, 0x50, 0x05, 0x00, 0x00,
+        0x08, 0x00, 0x0b, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x0a, 0x00, 0x10, 0x00, 0x63, 0x75, 0x62, 0x69,
+        0x63, 0x00, 0x00, 0x00, 0x08, 0x00, 0x04, 0x00,
+        0x06, 0x00, 0x00, 0x00,
+    ];
+
+    // Where the payload of RTA_METRICS is in the route
+    const METRICS: std::ops::Range<usize> = 32..84;
+
+    fn kernel_metrics() -> Result<RouteMetrics> {
+        RouteMetrics::builder()
+            .lock(RouteMetricsLock::MTU | RouteMetricsLock::CONGESTION_CONTROL)
+            .mtu(1400u32)
+            .rtt(800u32)
+            .advmss(1360u32)
+            .initcwnd(10u32)
+            .congestion_control("cubic")
+            .build()
+    }
+
+    #[test]
+    fn kernel_metrics_are_parsed() -> Result<()> {
+        let route = parse_route(KERNEL_ROUTE)?;
+        assert_eq!(route.metrics, Some(kernel_metrics()?));
+        Ok(())
+    }
+
+    #[test]
+    fn metrics_are_serialized_like_the_kernel() -> Result<()> {
+        let bytes = serialize_metrics(&kernel_metrics()?);
+        assert_eq!(bytes, &KERNEL_ROUTE[METRICS]);
+        Ok(())
+    }
+
+    // Each metric is locked by the bit of its RTAX_* type
+    #[test]
+    fn lock_bits_match_metric_types() {
+        #[rustfmt::skip]
+        let locks = [
+            (RouteMetricsLock::MTU, RouteMetricType::Mtu),
+            (RouteMetricsLock::WINDOW, RouteMetricType::Window),
+            (RouteMetricsLock::RTT, RouteMetricType::Rtt),
+            (RouteMetricsLock::RTTVAR, RouteMetricType::RttVar),
+            (RouteMetricsLock::SSTHRESH, RouteMetricType::Ssthresh),
+            (RouteMetricsLock::CWND, RouteMetricType::Cwnd),
+            (RouteMetricsLock::ADVMSS, RouteMetricType::Advmss),
+            (RouteMetricsLock::REORDERING, RouteMetricType::Reordering),
+            (RouteMetricsLock::HOPLIMIT, RouteMetricType::Hoplimit),
+            (RouteMetricsLock::INITCWND, RouteMetricType::Initcwnd),
+            (RouteMetricsLock::RTO_MIN, RouteMetricType::RtoMin),
+            (RouteMetricsLock::INITRWND, RouteMetricType::Initrwnd),
+            (RouteMetricsLock::CONGESTION_CONTROL, RouteMetricType::CongestionControl),
+        ];
+        for (lock, typ) in locks {
+            assert_eq!(lock.bits(), 1 << u16::from(typ));
+        }
+    }
+
+    #[test]
+    fn metrics_round_trip() -> Result<()> {
+        let metrics = RouteMetrics::builder()
+            .lock(RouteMetricsLock::all() | RouteMetricsLock::from_bits_retain(1 << 30))
+            .mtu(9000u32)
+            .window(65535u32)
+            .rtt(80u32)
+            .rttvar(40u32)
+            .ssthresh(100u32)
+            .cwnd(20u32)
+            .advmss(8960u32)
+            .reordering(3u32)
+            .hoplimit(64u32)
+            .initcwnd(10u32)
+            .features(1u32)
+            .rto_min(200u32)
+            .initrwnd(20u32)
+            .quickack(1u32)
+            .congestion_control("bbr")
+            .fastopen_no_cookie(1u32)
+            .build()?;
+        assert_eq!(deserialize_metrics(&serialize_metrics(&metrics))?, metrics);
+        Ok(())
+    }
+}
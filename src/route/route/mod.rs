@@ -1,5 +1,29 @@
 mod client;
 pub use client::*;
 
+mod encap;
+pub use encap::*;
+
+mod filter;
+pub use filter::*;
+
+mod kinds;
+pub use kinds::*;
+
+mod lookup;
+pub use lookup::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod multipath;
+pub use multipath::*;
+
+mod via;
+pub use via::*;
+
 pub mod types;
 pub use types::*;
+
+mod pretty;
+pub use pretty::*;
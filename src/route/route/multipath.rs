@@ -0,0 +1,353 @@
+use super::{deserialize_encap, serialize_encap, LwtEncap};
+use super::{deserialize_via, serialize_via, RouteAttrType, RouteVia};
+use crate::bytes::{aligned_size, deserialize_ip_addr, deserialize_u16, serialize_ip_addr};
+use crate::bytes::{deserialize_u32, push_attribute, push_nested_attribute, SliceReader};
+use crate::route::{deserialize_labels, serialize_labels, MplsLabel};
+use crate::{Error, Result};
+use bitflags::bitflags;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize, Serializer};
+use std::net::IpAddr;
+
+bitflags! {
+    /// State of a [`Nexthop`]. See `RTNH_F_*` in
+    /// [`rtnetlink.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/rtnetlink.h).
+    #[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Hash)]
+    pub struct NexthopFlags: u8 {
+        /// The next hop is unusable
+        const DEAD = 1;
+        /// Do recursive gateway lookup
+        const PERVASIVE = 2;
+        /// The gateway is on the link, even if no prefix says so
+        const ONLINK = 4;
+        /// Offloaded to hardware
+        const OFFLOAD = 8;
+        /// The carrier of the interface is down
+        const LINKDOWN = 16;
+        /// The gateway's address isn't resolved
+        const UNRESOLVED = 32;
+        /// Packets are trapped to the CPU by hardware
+        const TRAP = 64;
+    }
+}
+
+// Serialized as the raw bits, like the other flags fields of a route
+impl Serialize for NexthopFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.bits())
+    }
+}
+
+/// One of the next hops of a multipath route, like
+/// `nexthop via 10.0.0.1 dev eth0 weight 2` in `ip route`.
+///
+/// ```rust
+/// use netlink::route::route::{Nexthop, Route};
+/// use netlink::route::IpPrefix;
+/// use std::net::Ipv4Addr;
+///
+/// # fn main() -> netlink::Result<()> {
+/// // Send two thirds of the traffic through the first uplink
+/// let uplinks = vec![
+///     Nexthop::builder()
+///         .gateway(Ipv4Addr::new(10, 0, 0, 1))
+///         .output_interface_index(2)
+///         .weight(2u16)
+///         .build()?,
+///     Nexthop::builder()
+///         .gateway(Ipv4Addr::new(10, 0, 1, 1))
+///         .output_interface_index(3)
+///         .build()?,
+/// ];
+///
+/// let route = Route::builder()
+///     .dest("0.0.0.0/0".parse::<IpPrefix>()?)
+///     .nexthops(uplinks)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct Nexthop {
+    pub flags: NexthopFlags,
+    /// Share of the traffic relative to the other next hops, from 1 to 256.
+    /// Other weights fail with [`Error::ErrNexthopWeight`] when the route is
+    /// sent.
+    #[builder(default = "1")]
+    pub weight: u16,
+    pub output_interface_index: i32,
+    pub gateway: Option<IpAddr>,
+    /// Next hop of another family than the route, see [`RouteVia`]
+    pub via: Option<RouteVia>,
+    /// Labels that replace the label of an MPLS route through this next hop
+    pub new_dest: Vec<MplsLabel>,
+    /// Lightweight tunnel of the next hop, like `encap mpls 100`
+    pub encap: Option<LwtEncap>,
+    /// Realms of the route, for traffic classification
+    pub flow: Option<u32>,
+}
+
+impl Default for Nexthop {
+    fn default() -> Self {
+        Self {
+            flags: NexthopFlags::empty(),
+            weight: 1,
+            output_interface_index: 0,
+            gateway: None,
+            via: None,
+            new_dest: vec![],
+            encap: None,
+            flow: None,
+        }
+    }
+}
+
+impl Nexthop {
+    #[must_use]
+    pub fn builder() -> NexthopBuilder {
+        NexthopBuilder::default()
+    }
+}
+
+/// Header of every next hop in `RTA_MULTIPATH`. See `struct rtnexthop` in
+/// [`rtnetlink.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/rtnetlink.h).
+#[repr(C)]
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+struct NexthopHeader {
+    /// Length of the header and the attributes of the next hop
+    len: u16,
+    flags: u8,
+    /// Weight minus one
+    hops: u8,
+    ifindex: i32,
+}
+
+const NEXTHOP_HEADER_LEN: usize = 8;
+
+// The payload of RTA_MULTIPATH is an array of next hops, each of which is a
+// header followed by its own route attributes.
+pub(crate) fn deserialize_nexthops(payload: &[u8]) -> Result<Vec<Nexthop>> {
+    let mut reader = SliceReader::new(payload);
+    let mut nexthops = vec![];
+
+    while !reader.is_empty() {
+        let hdr = reader.read::<NexthopHeader>()?;
+        let attrs_len = usize::from(hdr.len)
+            .checked_sub(NEXTHOP_HEADER_LEN)
+            .ok_or(Error::ErrUnexpectedEof)?;
+        let mut attrs = SliceReader::new(reader.take(attrs_len)?);
+
+        let padding_len = aligned_size(attrs_len) - attrs_len;
+        reader.take(padding_len.min(reader.remaining()))?;
+
+        let mut nexthop = Nexthop {
+            flags: NexthopFlags::from_bits_retain(hdr.flags),
+            weight: u16::from(hdr.hops) + 1,
+            output_interface_index: hdr.ifindex,
+            ..Default::default()
+        };
+
+        // The encapsulation can only be decoded once its type is known
+        let mut encap_type = None;
+        let mut encap = None;
+
+        while !attrs.is_empty() {
+            let (typ, value) = attrs.read_attribute()?;
+            match typ {
+                typ if typ == RouteAttrType::Gateway.into() => {
+                    nexthop.gateway = Some(deserialize_ip_addr(value)?);
+                }
+                typ if typ == RouteAttrType::Via.into() => {
+                    nexthop.via = Some(deserialize_via(value)?);
+                }
+                typ if typ == RouteAttrType::NewDest.into() => {
+                    nexthop.new_dest = deserialize_labels(value)?;
+                }
+                typ if typ == RouteAttrType::EncapType.into() => {
+                    encap_type = Some(deserialize_u16(value)?);
+                }
+                typ if typ == RouteAttrType::Encap.into() => {
+                    encap = Some(value);
+                }
+                typ if typ == RouteAttrType::Flow.into() => {
+                    nexthop.flow = Some(deserialize_u32(value)?);
+                }
+                // Skip attributes added by kernels newer than this library
+                _ => {}
+            }
+        }
+
+        if let (Some(typ), Some(encap)) = (encap_type, encap) {
+            nexthop.encap = Some(deserialize_encap(typ, encap)?);
+        }
+        nexthops.push(nexthop);
+    }
+
+    Ok(nexthops)
+}
+
+// Fails if a weight is out of range, or a next hop doesn't fit in the u16
+// length of its header.
+pub(crate) fn serialize_nexthops(nexthops: &[Nexthop]) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+
+    for nexthop in nexthops {
+        // The header holds the weight minus one in a u8
+        let hops = nexthop
+            .weight
+            .checked_sub(1)
+            .and_then(|hops| u8::try_from(hops).ok())
+            .ok_or(Error::ErrNexthopWeight(nexthop.weight))?;
+
+        let mut attrs = vec![];
+        if let Some(addr) = &nexthop.gateway {
+            push_attribute(
+                &mut attrs,
+                RouteAttrType::Gateway.into(),
+                &serialize_ip_addr(addr),
+            );
+        }
+        if let Some(via) = &nexthop.via {
+            push_attribute(&mut attrs, RouteAttrType::Via.into(), &serialize_via(via));
+        }
+        if !nexthop.new_dest.is_empty() {
+            let labels = serialize_labels(&nexthop.new_dest);
+            push_attribute(&mut attrs, RouteAttrType::NewDest.into(), &labels);
+        }
+        if let Some(encap) = &nexthop.encap {
+            let typ = u16::from(encap.typ()).to_le_bytes();
+            push_attribute(&mut attrs, RouteAttrType::EncapType.into(), &typ);
+            let encap = serialize_encap(encap)?;
+            push_nested_attribute(&mut attrs, RouteAttrType::Encap.into(), &encap);
+        }
+        if let Some(flow) = nexthop.flow {
+            push_attribute(&mut attrs, RouteAttrType::Flow.into(), &flow.to_le_bytes());
+        }
+
+        let len = u16::try_from(NEXTHOP_HEADER_LEN + attrs.len())
+            .map_err(|_| Error::ErrValueConversion)?;
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.push(nexthop.flags.bits());
+        bytes.push(hops);
+        bytes.extend_from_slice(&nexthop.output_interface_index.to_le_bytes());
+        bytes.append(&mut attrs);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::route::{parse_route, serialize_route, MplsEncap, Route};
+    use crate::route::IpPrefix;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    // RTM_NEWROUTE payload of `ip route add 198.51.100.0/24 nexthop via
+    // 10.10.0.1 dev nltest0 weight 2 nexthop via 10.10.1.1 dev nltest1
+    // onlink`, as dumped by the kernel
+    #[rustfmt::skip]
+    const KERNEL_ROUTE: &[u8] = &[
+        0x02, 0x18, 0x00, 0x00, 0xfe, 0x03, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0xfe, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00,
+        0xc6, 0x33, 0x64, 0x00, 0x24, 0x00, 0x09, 0x00,
+        0x10, 0x00, 0x00, 0x01, 0x06, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x05, 0x00, 0x0a, 0x0a, 0x00, 0x01,
+        0x10, 0x00, 0x04, 0x00, 0x05, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x05, 0x00, 0x0a, 0x0a, 0x01, 0x01,
+    ];
+
+    // Where the payload of RTA_MULTIPATH starts
+    const MULTIPATH_OFFSET: usize = 32;
+
+    fn kernel_nexthops() -> Result<Vec<Nexthop>> {
+        Ok(vec![
+            Nexthop::builder()
+                .gateway(Ipv4Addr::new(10, 10, 0, 1))
+                .output_interface_index(6)
+                .weight(2u16)
+                .build()?,
+            Nexthop::builder()
+                .gateway(Ipv4Addr::new(10, 10, 1, 1))
+                .output_interface_index(5)
+                .flags(NexthopFlags::ONLINK)
+                .build()?,
+        ])
+    }
+
+    #[test]
+    fn kernel_nexthops_are_parsed() -> Result<()> {
+        let route = parse_route(KERNEL_ROUTE)?;
+        assert_eq!(route.nexthops, kernel_nexthops()?);
+        Ok(())
+    }
+
+    #[test]
+    fn nexthops_are_serialized_like_the_kernel() -> Result<()> {
+        let bytes = serialize_nexthops(&kernel_nexthops()?)?;
+        assert_eq!(bytes, &KERNEL_ROUTE[MULTIPATH_OFFSET..]);
+        Ok(())
+    }
+
+    #[test]
+    fn nexthops_round_trip() -> Result<()> {
+        let encap = MplsEncap::builder()
+            .labels(vec![MplsLabel::new(100)?])
+            .ttl(8)
+            .build()?;
+        let nexthops = vec![
+            Nexthop::builder()
+                .via(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))
+                .output_interface_index(2)
+                .weight(256u16)
+                .encap(LwtEncap::Mpls(encap))
+                .flow(7u32)
+                .build()?,
+            Nexthop::builder()
+                .output_interface_index(3)
+                .new_dest(vec![MplsLabel::new(200)?, MplsLabel::new(300)?])
+                .flags(NexthopFlags::DEAD | NexthopFlags::LINKDOWN)
+                .build()?,
+        ];
+        assert_eq!(
+            deserialize_nexthops(&serialize_nexthops(&nexthops)?)?,
+            nexthops
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_weight_is_rejected() -> Result<()> {
+        for weight in [0, 257, u16::MAX] {
+            let nexthop = Nexthop::builder().weight(weight).build()?;
+            assert!(matches!(
+                serialize_nexthops(&[nexthop]),
+                Err(Error::ErrNexthopWeight(w)) if w == weight
+            ));
+        }
+
+        let route = Route::builder()
+            .dest("10.0.0.0/8".parse::<IpPrefix>()?)
+            .nexthops(vec![Nexthop::builder().weight(0u16).build()?])
+            .build()?;
+        assert!(matches!(
+            serialize_route(&route),
+            Err(Error::ErrNexthopWeight(0))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_nexthop_is_rejected() -> Result<()> {
+        // 16384 labels are 64 KiB, which with the headers don't fit in rtnh_len
+        let labels = vec![MplsLabel::new(100)?; 16384];
+        let nexthop = Nexthop::builder().new_dest(labels).build()?;
+        assert!(matches!(
+            serialize_nexthops(&[nexthop]),
+            Err(Error::ErrValueConversion)
+        ));
+        Ok(())
+    }
+}
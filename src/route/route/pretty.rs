@@ -0,0 +1,107 @@
+use super::{RouteMessage, RouteMessageType};
+use crate::bytes::{deserialize_repr, SliceReader};
+use crate::pretty::{decode_attributes, family_name, AttrKind, AttrSpec, Field, FieldValue};
+use crate::pretty::{FlagKind, MessageDecoder};
+use crate::Result;
+
+/// Decodes `RTM_*ROUTE` messages. See [`crate::DecoderRegistry`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RouteDecoder;
+
+#[rustfmt::skip]
+const RTAX_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "RTAX_LOCK", AttrKind::U32),
+    AttrSpec::new(2, "RTAX_MTU", AttrKind::U32),
+    AttrSpec::new(3, "RTAX_WINDOW", AttrKind::U32),
+    AttrSpec::new(4, "RTAX_RTT", AttrKind::U32),
+    AttrSpec::new(5, "RTAX_RTTVAR", AttrKind::U32),
+    AttrSpec::new(6, "RTAX_SSTHRESH", AttrKind::U32),
+    AttrSpec::new(7, "RTAX_CWND", AttrKind::U32),
+    AttrSpec::new(8, "RTAX_ADVMSS", AttrKind::U32),
+    AttrSpec::new(9, "RTAX_REORDERING", AttrKind::U32),
+    AttrSpec::new(10, "RTAX_HOPLIMIT", AttrKind::U32),
+    AttrSpec::new(11, "RTAX_INITCWND", AttrKind::U32),
+    AttrSpec::new(12, "RTAX_FEATURES", AttrKind::U32),
+    AttrSpec::new(13, "RTAX_RTO_MIN", AttrKind::U32),
+    AttrSpec::new(14, "RTAX_INITRWND", AttrKind::U32),
+    AttrSpec::new(15, "RTAX_QUICKACK", AttrKind::U32),
+    AttrSpec::new(16, "RTAX_CC_ALGO", AttrKind::Str),
+    AttrSpec::new(17, "RTAX_FASTOPEN_NO_COOKIE", AttrKind::U32),
+];
+
+#[rustfmt::skip]
+const RTA_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "RTA_DST", AttrKind::Addr),
+    AttrSpec::new(2, "RTA_SRC", AttrKind::Addr),
+    AttrSpec::new(3, "RTA_IIF", AttrKind::U32),
+    AttrSpec::new(4, "RTA_OIF", AttrKind::U32),
+    AttrSpec::new(5, "RTA_GATEWAY", AttrKind::Addr),
+    AttrSpec::new(6, "RTA_PRIORITY", AttrKind::U32),
+    AttrSpec::new(7, "RTA_PREFSRC", AttrKind::Addr),
+    AttrSpec::new(8, "RTA_METRICS", AttrKind::Nested(RTAX_ATTRS)),
+    AttrSpec::new(9, "RTA_MULTIPATH", AttrKind::Bytes),
+    AttrSpec::new(10, "RTA_PROTOINFO", AttrKind::Bytes),
+    AttrSpec::new(11, "RTA_FLOW", AttrKind::U32),
+    AttrSpec::new(12, "RTA_CACHEINFO", AttrKind::Bytes),
+    AttrSpec::new(13, "RTA_SESSION", AttrKind::Bytes),
+    AttrSpec::new(14, "RTA_MP_ALGO", AttrKind::Bytes),
+    AttrSpec::new(15, "RTA_TABLE", AttrKind::U32),
+    AttrSpec::new(16, "RTA_MARK", AttrKind::U32),
+    AttrSpec::new(17, "RTA_MFC_STATS", AttrKind::Bytes),
+    AttrSpec::new(18, "RTA_VIA", AttrKind::Bytes),
+    AttrSpec::new(19, "RTA_NEWDST", AttrKind::Bytes),
+    AttrSpec::new(20, "RTA_PREF", AttrKind::U8),
+    AttrSpec::new(21, "RTA_ENCAP_TYPE", AttrKind::U16),
+    AttrSpec::new(22, "RTA_ENCAP", AttrKind::Bytes),
+    AttrSpec::new(23, "RTA_EXPIRES", AttrKind::U32),
+    AttrSpec::new(24, "RTA_PAD", AttrKind::Bytes),
+    AttrSpec::new(25, "RTA_UID", AttrKind::U32),
+    AttrSpec::new(26, "RTA_TTL_PROPAGATE", AttrKind::U8),
+    AttrSpec::new(27, "RTA_IP_PROTO", AttrKind::U8),
+    AttrSpec::new(28, "RTA_SPORT", AttrKind::Bytes),
+    AttrSpec::new(29, "RTA_DPORT", AttrKind::Bytes),
+    AttrSpec::new(30, "RTA_NH_ID", AttrKind::U32),
+];
+
+impl MessageDecoder for RouteDecoder {
+    fn type_name(&self, typ: u16) -> Option<&'static str> {
+        match deserialize_repr::<RouteMessageType>(typ)? {
+            RouteMessageType::NewRoute => Some("RTM_NEWROUTE"),
+            RouteMessageType::DelRoute => Some("RTM_DELROUTE"),
+            RouteMessageType::GetRoute => Some("RTM_GETROUTE"),
+            _ => None,
+        }
+    }
+
+    fn flag_kind(&self, typ: u16) -> FlagKind {
+        match deserialize_repr::<RouteMessageType>(typ) {
+            Some(RouteMessageType::NewRoute) => FlagKind::New,
+            Some(RouteMessageType::DelRoute) => FlagKind::Delete,
+            Some(RouteMessageType::GetRoute) => FlagKind::Get,
+            _ => FlagKind::None,
+        }
+    }
+
+    fn decode(&self, _typ: u16, payload: &[u8]) -> Result<Vec<FieldValue>> {
+        let mut reader = SliceReader::new(payload);
+        let rtmsg = reader.read::<RouteMessage>()?;
+
+        let header = vec![
+            Field::text("rtm_family", &family_name(rtmsg.family)),
+            Field::text("rtm_dst_len", &rtmsg.dst_len),
+            Field::text("rtm_src_len", &rtmsg.src_len),
+            Field::text("rtm_tos", &rtmsg.tos),
+            Field::text("rtm_table", &rtmsg.table),
+            Field::text("rtm_protocol", &rtmsg.protocol),
+            Field::text("rtm_scope", &rtmsg.scope),
+            Field::text("rtm_type", &rtmsg.typ),
+            Field::text("rtm_flags", &format!("{:#x}", rtmsg.flags)),
+        ];
+
+        let attrs = decode_attributes(reader.take(reader.remaining())?, RTA_ATTRS)?;
+        Ok(vec![
+            FieldValue::Struct(header),
+            FieldValue::Attributes(attrs),
+        ])
+    }
+}
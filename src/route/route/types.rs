@@ -1,13 +1,19 @@
-use crate::bytes::{deserialize_i16, deserialize_i32, deserialize_i8, deserialize_ip_addr};
+use super::{deserialize_metrics, deserialize_nexthops, serialize_metrics, serialize_nexthops};
+use super::{deserialize_via, serialize_via, Nexthop, RouteMetrics, RouteVia};
+use crate::bytes::SliceReader;
+use crate::bytes::{deserialize_i32, deserialize_ip_addr, deserialize_u16, serialize_ip_addr};
+use crate::bytes::{deserialize_u16_be, deserialize_u32, deserialize_u64, deserialize_u8};
+use crate::route::{deserialize_labels, serialize_labels, MplsLabel};
 use crate::{Error, Result};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::net::IpAddr;
+use std::time::Duration;
 
 /// Types of route messages.
 #[repr(u16)]
-#[derive(Debug, PartialEq, Copy, Clone, Serialize_repr, Deserialize_repr)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize_repr, Deserialize_repr)]
 pub enum RouteMessageType {
     // Link
     NewLink = 16,
@@ -22,6 +28,14 @@ pub enum RouteMessageType {
     NewRoute = 24,
     DelRoute = 25,
     GetRoute = 26,
+    // Rule
+    NewRule = 32,
+    DelRule = 33,
+    GetRule = 34,
+    // Nexthop
+    NewNexthop = 104,
+    DelNexthop = 105,
+    GetNexthop = 106,
 }
 
 impl From<RouteMessageType> for u16 {
@@ -46,7 +60,8 @@ pub struct RouteMessage {
     pub protocol: u8,
     pub scope: u8,
     pub typ: u8,
-    pub flags: u8,
+    /// `RTM_F_*` flags, like [`crate::route::RTM_F_FIB_MATCH`]
+    pub flags: u32,
 }
 
 impl RouteMessage {
@@ -56,6 +71,63 @@ impl RouteMessage {
     }
 }
 
+/// Clock ticks per second of the times in [`RouteCacheInfo`]. This is
+/// `USER_HZ` from the kernel's `include/asm-generic/param.h`, which is 100 on
+/// every architecture: `rtnl_put_cacheinfo()` converts jiffies to it with
+/// `jiffies_to_clock_t()`, so it doesn't depend on the kernel's `CONFIG_HZ`.
+const USER_HZ: u64 = 100;
+
+/// Usage and expiry of a route, from `RTA_CACHEINFO`.
+///
+/// See `struct rta_cacheinfo` in
+/// [`rtnetlink.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/rtnetlink.h).
+/// Times are in clock ticks, which are hundredths of a second.
+#[repr(C)]
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RouteCacheInfo {
+    pub clntref: u32,
+    /// Ticks since the route was last used
+    pub lastuse: u32,
+    /// Ticks until the route expires, or 0 if it doesn't
+    pub expires: i32,
+    pub error: u32,
+    pub used: u32,
+    pub id: u32,
+    pub ts: u32,
+    pub tsage: u32,
+}
+
+impl RouteCacheInfo {
+    /// Time left until the route is removed, or `None` if it doesn't
+    /// expire. Routes that are past due are reported as expiring now.
+    #[must_use]
+    pub fn expires_in(&self) -> Option<Duration> {
+        if self.expires == 0 {
+            return None;
+        }
+        let ticks = u64::try_from(self.expires).unwrap_or_default();
+        Some(Duration::from_millis(ticks * 1000 / USER_HZ))
+    }
+
+    /// Time since the route was last used.
+    #[must_use]
+    pub fn last_used(&self) -> Duration {
+        Duration::from_millis(u64::from(self.lastuse) * 1000 / USER_HZ)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        #[rustfmt::skip]
+        let fields = [
+            self.clntref, self.lastuse, self.expires.cast_unsigned(), self.error,
+            self.used, self.id, self.ts, self.tsage,
+        ];
+        fields
+            .iter()
+            .flat_map(|field| field.to_le_bytes())
+            .collect()
+    }
+}
+
 /// Attribute of a request or response. See [`RouteAttrValue`] to understand how
 /// to interpret the data pointed at by this header.
 #[repr(C)]
@@ -96,6 +168,19 @@ pub enum RouteAttrType {
     EncapType = 21,
     Encap = 22,
     Expires = 23,
+    Pad = 24,
+    Uid = 25,
+    TtlPropagate = 26,
+    IpProto = 27,
+    SourcePort = 28,
+    DestPort = 29,
+    NexthopId = 30,
+}
+
+impl From<RouteAttrType> for u16 {
+    fn from(typ: RouteAttrType) -> Self {
+        typ as u16
+    }
 }
 
 /// Strongly-typed [`RouteAttr`].
@@ -103,35 +188,86 @@ pub enum RouteAttrType {
 pub enum RouteAttrValue {
     Unspec,
     Dest(IpAddr),
+    /// `RTA_DST` of an MPLS route, which is a label rather than an address.
+    /// [`RouteAttrValue::deserialize`] can't tell them apart, but parsing a
+    /// route does.
+    MplsDest(MplsLabel),
     Source(IpAddr),
     InputInterfaceIndex(i32),
     OutputInterfaceIndex(i32),
     Gateway(IpAddr),
     Priority(i32),
     PreferredSourceAddr(IpAddr),
-    Metrics(i32),
-    // Unknown
-    Multipath(Vec<u8>), // unknown
+    /// Nested `RTAX_*` attributes
+    Metrics(RouteMetrics),
+    /// Next hops of a multipath route
+    Multipath(Vec<Nexthop>),
     // No longer use
     ProtoInfo(Vec<u8>), // No longer used
     Flow(i32),
-    // rta_info
-    CacheInfo(Vec<u8>),
+    CacheInfo(RouteCacheInfo),
     // No longer used
     Session(Vec<u8>),
     // No longer used
     MpAlgo(Vec<u8>),
-    Table(i32),
-    Mark(i32),
+    Table(u32),
+    Mark(u32),
     // mfc_stats
     MfcStats(Vec<u8>),
-    // rt_via
-    Via(Vec<u8>),
-    NewDest(IpAddr),
-    Pref(i8),
-    EncapType(i16),
+    Via(RouteVia),
+    /// Labels that replace the label of an MPLS route
+    NewDest(Vec<MplsLabel>),
+    /// See [`RoutePreference`]
+    Pref(u8),
+    /// See [`LwtEncapType`]
+    EncapType(u16),
+    /// Attributes of the encapsulation, which depend on its type. See
+    /// [`LwtEncap`].
     Encap(Vec<u8>),
-    Expires(i32),
+    /// Seconds, sent as a u32. The kernel reports a u64 for multicast routes,
+    /// which is rejected if it doesn't fit.
+    Expires(u32),
+    Pad,
+    Uid(u32),
+    TtlPropagate(u8),
+    IpProto(u8),
+    /// In host byte order, though it's sent in network byte order
+    SourcePort(u16),
+    /// In host byte order, though it's sent in network byte order
+    DestPort(u16),
+    NexthopId(u32),
+}
+
+/// Preference of an IPv6 default route learned from a router advertisement,
+/// carried in `RTA_PREF`. See RFC 4191.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+pub enum RoutePreference {
+    Medium,
+    High,
+    Low,
+}
+
+impl RoutePreference {
+    /// Interpret the value of `RTA_PREF`. The reserved value 2 is invalid.
+    #[must_use]
+    pub fn from_raw(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Medium),
+            1 => Some(Self::High),
+            3 => Some(Self::Low),
+            _ => None,
+        }
+    }
+
+    /// The value of `RTA_PREF`.
+    #[must_use]
+    pub fn raw(self) -> u8 {
+        match self {
+            Self::Medium => 0,
+            Self::High => 1,
+            Self::Low => 3,
+        }
+    }
 }
 
 /// Statistics about a link.
@@ -195,10 +331,10 @@ impl RouteAttrValue {
                 deserialize_ip_addr(payload).map(Self::PreferredSourceAddr)
             }
             RouteAttrType::Metrics => {
-                deserialize_i32(payload).map(Self::Metrics)
+                deserialize_metrics(payload).map(Self::Metrics)
             }
             RouteAttrType::Multipath => {
-                Ok(Self::Multipath(payload.to_vec()))
+                deserialize_nexthops(payload).map(Self::Multipath)
             }
             RouteAttrType::ProtoInfo => {
                 Ok(Self::ProtoInfo(payload.to_vec()))
@@ -207,7 +343,7 @@ impl RouteAttrValue {
                 deserialize_i32(payload).map(Self::Flow)
             }
             RouteAttrType::CacheInfo => {
-                Ok(Self::CacheInfo(payload.to_vec()))
+                SliceReader::new(payload).read::<RouteCacheInfo>().map(Self::CacheInfo)
             }
             RouteAttrType::Session => {
                 Ok(Self::Session(payload.to_vec()))
@@ -216,32 +352,163 @@ impl RouteAttrValue {
                 Ok(Self::MpAlgo(payload.to_vec()))
             }
             RouteAttrType::Table => {
-                deserialize_i32(payload).map(Self::Table)
+                deserialize_u32(payload).map(Self::Table)
             }
             RouteAttrType::Mark => {
-                deserialize_i32(payload).map(Self::Mark)
+                deserialize_u32(payload).map(Self::Mark)
             }
             RouteAttrType::MfcStats => {
                 Ok(Self::MfcStats(payload.to_vec()))
             }
             RouteAttrType::Via => {
-                Ok(Self::Via(payload.to_vec()))
+                deserialize_via(payload).map(Self::Via)
             }
             RouteAttrType::NewDest => {
-                deserialize_ip_addr(payload).map(Self::NewDest)
+                deserialize_labels(payload).map(Self::NewDest)
             }
             RouteAttrType::Pref => {
-                deserialize_i8(payload).map(Self::Pref)
+                deserialize_u8(payload).map(Self::Pref)
             }
             RouteAttrType::EncapType => {
-                deserialize_i16(payload).map(Self::EncapType)
+                deserialize_u16(payload).map(Self::EncapType)
             }
             RouteAttrType::Encap => {
                 Ok(Self::Encap(payload.to_vec()))
             }
             RouteAttrType::Expires => {
-                deserialize_i32(payload).map(Self::Expires)
+                // Multicast routes report a 64-bit value
+                let value = match deserialize_u32(payload) {
+                    Ok(value) => value,
+                    Err(_) => u32::try_from(deserialize_u64(payload)?)
+                        .map_err(|_| Error::ErrValueConversion)?,
+                };
+                Ok(Self::Expires(value))
+            }
+            RouteAttrType::Pad => {
+                Ok(Self::Pad)
+            }
+            RouteAttrType::Uid => {
+                deserialize_u32(payload).map(Self::Uid)
+            }
+            RouteAttrType::TtlPropagate => {
+                deserialize_u8(payload).map(Self::TtlPropagate)
+            }
+            RouteAttrType::IpProto => {
+                deserialize_u8(payload).map(Self::IpProto)
+            }
+            RouteAttrType::SourcePort => {
+                deserialize_u16_be(payload).map(Self::SourcePort)
+            }
+            RouteAttrType::DestPort => {
+                deserialize_u16_be(payload).map(Self::DestPort)
+            }
+            RouteAttrType::NexthopId => {
+                deserialize_u32(payload).map(Self::NexthopId)
             }
         }
     }
 }
+
+#[rustfmt::skip]
+impl RouteAttrValue {
+    pub(crate) fn serialize(&self) -> Result<(RouteAttrType, Vec<u8>)> {
+        Ok(match self {
+            Self::Unspec => {
+                (RouteAttrType::Unspec, vec![])
+            }
+            Self::Dest(addr) => {
+                (RouteAttrType::Dest, serialize_ip_addr(addr))
+            }
+            Self::MplsDest(label) => {
+                (RouteAttrType::Dest, serialize_labels(&[*label]))
+            }
+            Self::Source(addr) => {
+                (RouteAttrType::Source, serialize_ip_addr(addr))
+            }
+            Self::InputInterfaceIndex(value) => {
+                (RouteAttrType::InputInterfaceIndex, value.to_le_bytes().to_vec())
+            }
+            Self::OutputInterfaceIndex(value) => {
+                (RouteAttrType::OutputInterfaceIndex, value.to_le_bytes().to_vec())
+            }
+            Self::Gateway(addr) => {
+                (RouteAttrType::Gateway, serialize_ip_addr(addr))
+            }
+            Self::Priority(value) => {
+                (RouteAttrType::Priority, value.to_le_bytes().to_vec())
+            }
+            Self::PreferredSourceAddr(addr) => {
+                (RouteAttrType::PreferredSourceAddr, serialize_ip_addr(addr))
+            }
+            Self::Metrics(metrics) => {
+                (RouteAttrType::Metrics, serialize_metrics(metrics))
+            }
+            Self::Multipath(nexthops) => {
+                (RouteAttrType::Multipath, serialize_nexthops(nexthops)?)
+            }
+            Self::ProtoInfo(bytes) => {
+                (RouteAttrType::ProtoInfo, bytes.clone())
+            }
+            Self::Flow(value) => {
+                (RouteAttrType::Flow, value.to_le_bytes().to_vec())
+            }
+            Self::CacheInfo(info) => {
+                (RouteAttrType::CacheInfo, info.to_bytes())
+            }
+            Self::Session(bytes) => {
+                (RouteAttrType::Session, bytes.clone())
+            }
+            Self::MpAlgo(bytes) => {
+                (RouteAttrType::MpAlgo, bytes.clone())
+            }
+            Self::Table(value) => {
+                (RouteAttrType::Table, value.to_le_bytes().to_vec())
+            }
+            Self::Mark(value) => {
+                (RouteAttrType::Mark, value.to_le_bytes().to_vec())
+            }
+            Self::MfcStats(bytes) => {
+                (RouteAttrType::MfcStats, bytes.clone())
+            }
+            Self::Via(via) => {
+                (RouteAttrType::Via, serialize_via(via))
+            }
+            Self::NewDest(labels) => {
+                (RouteAttrType::NewDest, serialize_labels(labels))
+            }
+            Self::Pref(value) => {
+                (RouteAttrType::Pref, value.to_le_bytes().to_vec())
+            }
+            Self::EncapType(value) => {
+                (RouteAttrType::EncapType, value.to_le_bytes().to_vec())
+            }
+            Self::Encap(bytes) => {
+                (RouteAttrType::Encap, bytes.clone())
+            }
+            Self::Expires(value) => {
+                (RouteAttrType::Expires, value.to_le_bytes().to_vec())
+            }
+            Self::Pad => {
+                (RouteAttrType::Pad, vec![])
+            }
+            Self::Uid(value) => {
+                (RouteAttrType::Uid, value.to_le_bytes().to_vec())
+            }
+            Self::TtlPropagate(value) => {
+                (RouteAttrType::TtlPropagate, vec![*value])
+            }
+            Self::IpProto(value) => {
+                (RouteAttrType::IpProto, vec![*value])
+            }
+            Self::SourcePort(port) => {
+                (RouteAttrType::SourcePort, port.to_be_bytes().to_vec())
+            }
+            Self::DestPort(port) => {
+                (RouteAttrType::DestPort, port.to_be_bytes().to_vec())
+            }
+            Self::NexthopId(value) => {
+                (RouteAttrType::NexthopId, value.to_le_bytes().to_vec())
+            }
+        })
+    }
+}
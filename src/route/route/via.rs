@@ -0,0 +1,124 @@
+use crate::bytes::{deserialize_ip_addr, serialize_ip_addr};
+use crate::route::AddressFamily;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Next hop in `RTA_VIA`, which unlike a gateway can be of another family
+/// than the route. IPv4 routes can go via an IPv6 next hop, and MPLS routes
+/// always use it.
+///
+/// See `struct rtvia` in
+/// [`rtnetlink.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/rtnetlink.h).
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize)]
+pub enum RouteVia {
+    Ip(IpAddr),
+    /// Address of another family, like a MAC address for `AF_PACKET`
+    Other {
+        family: u16,
+        addr: Vec<u8>,
+    },
+}
+
+impl RouteVia {
+    #[must_use]
+    pub fn family(&self) -> u16 {
+        match self {
+            Self::Ip(addr) => u8::from(AddressFamily::of(addr)).into(),
+            Self::Other { family, .. } => *family,
+        }
+    }
+}
+
+impl From<IpAddr> for RouteVia {
+    fn from(addr: IpAddr) -> Self {
+        Self::Ip(addr)
+    }
+}
+
+impl From<Ipv4Addr> for RouteVia {
+    fn from(addr: Ipv4Addr) -> Self {
+        Self::Ip(addr.into())
+    }
+}
+
+impl From<Ipv6Addr> for RouteVia {
+    fn from(addr: Ipv6Addr) -> Self {
+        Self::Ip(addr.into())
+    }
+}
+
+// The address follows a u16 family
+pub(crate) fn deserialize_via(payload: &[u8]) -> Result<RouteVia> {
+    let (family, addr) = payload
+        .split_first_chunk::<2>()
+        .ok_or(Error::ErrUnexpectedEof)?;
+    let family = u16::from_le_bytes(*family);
+
+    let is_ip = [AddressFamily::Inet, AddressFamily::Inet6]
+        .iter()
+        .any(|ip| u16::from(u8::from(*ip)) == family);
+    if is_ip {
+        return deserialize_ip_addr(addr).map(RouteVia::Ip);
+    }
+
+    Ok(RouteVia::Other {
+        family,
+        addr: addr.to_vec(),
+    })
+}
+
+pub(crate) fn serialize_via(via: &RouteVia) -> Vec<u8> {
+    let mut bytes = via.family().to_le_bytes().to_vec();
+    match via {
+        RouteVia::Ip(addr) => bytes.append(&mut serialize_ip_addr(addr)),
+        RouteVia::Other { addr, .. } => bytes.extend_from_slice(addr),
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ip_via_round_trips() -> Result<()> {
+        let v4 = RouteVia::from(Ipv4Addr::new(10, 0, 0, 2));
+        let bytes = serialize_via(&v4);
+        assert_eq!(bytes, [0x02, 0x00, 0x0a, 0x00, 0x00, 0x02]);
+        assert_eq!(deserialize_via(&bytes)?, v4);
+
+        let v6 = RouteVia::from(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        let bytes = serialize_via(&v6);
+        assert_eq!(bytes.len(), 18);
+        assert_eq!(bytes[..2], [0x0a, 0x00]);
+        assert_eq!(deserialize_via(&bytes)?, v6);
+        Ok(())
+    }
+
+    #[test]
+    fn other_via_round_trips() -> Result<()> {
+        // A MAC address, as `via packet` adds it on MPLS routes
+        const AF_PACKET: u16 = 17;
+        let via = RouteVia::Other {
+            family: AF_PACKET,
+            addr: vec![0x02, 0x00, 0x00, 0x00, 0x00, 0x01],
+        };
+        assert_eq!(via.family(), AF_PACKET);
+
+        let bytes = serialize_via(&via);
+        assert_eq!(bytes[..2], [0x11, 0x00]);
+        assert_eq!(deserialize_via(&bytes)?, via);
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_via_is_rejected() {
+        assert!(matches!(
+            deserialize_via(&[0x02]),
+            Err(Error::ErrUnexpectedEof)
+        ));
+        // AF_INET with a truncated address
+        assert!(deserialize_via(&[0x02, 0x00, 0x0a, 0x00]).is_err());
+    }
+}
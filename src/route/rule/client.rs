@@ -0,0 +1,523 @@
+use super::{RuleAction, RuleAttrType, RuleAttrValue, RuleMessage, FIB_RULE_INVERT};
+use crate::bytes::{deserialize_repr, serialize_aligned, serialize_attribute, SliceReader};
+use crate::route::route::{header_table, RouteMessageType, RouteProtocol, RouteTable};
+use crate::route::{AddressFamily, IpPrefix, OperationError, RequestContext};
+use crate::transport::Transport;
+use crate::{
+    DeleteFlags, Error, Flags, GetFlags, MessageFlags, NetlinkMessage, NetlinkRequest,
+    NetlinkStream, NewFlags, Result,
+};
+use derive_builder::Builder;
+use nix::errno::Errno;
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+/// A policy routing rule, as listed by [`NetlinkStream::list_rules`] or
+/// installed by [`NetlinkStream::add_rule`]. Rules are evaluated in order of
+/// priority, and select the routing table of the packets they match.
+///
+/// ```rust
+/// use netlink::route::route::RouteTable;
+/// use netlink::route::rule::Rule;
+/// use netlink::route::IpPrefix;
+///
+/// # fn main() -> netlink::Result<()> {
+/// // ip rule add from 192.168.2.0/24 lookup 100 priority 1000
+/// let rule = Rule::builder()
+///     .source("192.168.2.0/24".parse::<IpPrefix>()?)
+///     .table(RouteTable::Id(100))
+///     .priority(1000u32)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Debug, Default, Builder, Serialize)]
+#[builder(default, setter(strip_option, into), build_fn(error = "Error"))]
+pub struct Rule {
+    pub family: AddressFamily,
+    /// Rules with a lower priority are evaluated first. The kernel picks one
+    /// below the existing rules if it isn't set.
+    pub priority: Option<u32>,
+    pub action: RuleAction,
+    /// Routing table of a [`RuleAction::ToTable`] rule, from `FRA_TABLE`
+    /// since tables above 255 don't fit in the header
+    pub table: RouteTable,
+    /// Source prefix, like `from` in `ip rule`. A rule without one matches
+    /// every source.
+    pub source: Option<IpPrefix>,
+    /// Destination prefix, like `to` in `ip rule`
+    pub dest: Option<IpPrefix>,
+    pub tos: u8,
+    /// Match the packets that the selectors don't match, like `not` in
+    /// `ip rule`
+    pub invert: bool,
+    pub fwmark: Option<u32>,
+    /// Bits of the mark compared to `fwmark`, all of them if not set
+    pub fwmask: Option<u32>,
+    /// Name of the interface packets are received on, like `iif` in `ip rule`
+    pub input_interface: Option<String>,
+    /// Name of the interface packets are sent from, like `oif` in `ip rule`
+    pub output_interface: Option<String>,
+    /// Priority of the rule that a [`RuleAction::Goto`] rule jumps to
+    pub goto: Option<u32>,
+    /// Reject routes with a prefix length of this or less, like
+    /// `suppress_prefixlength` in `ip rule`
+    pub suppress_prefixlen: Option<u32>,
+    /// Reject routes whose output interface is in this group
+    pub suppress_ifgroup: Option<u32>,
+    /// UIDs of the sockets the rule applies to
+    pub uid_range: Option<RangeInclusive<u32>>,
+    /// IP protocol, like 6 for TCP
+    pub ip_proto: Option<u8>,
+    pub source_port_range: Option<RangeInclusive<u16>>,
+    pub dest_port_range: Option<RangeInclusive<u16>>,
+    /// What installed the rule
+    pub protocol: RouteProtocol,
+}
+
+impl Rule {
+    #[must_use]
+    pub fn builder() -> RuleBuilder {
+        RuleBuilder::default()
+    }
+}
+
+impl<T: Transport> NetlinkStream<T> {
+    /// List the IPv4 policy routing rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure.
+    pub fn list_rules(&mut self) -> Result<Vec<Rule>> {
+        self.execute(&ListRules::default())
+    }
+
+    /// List the rules of an address family, like [`AddressFamily::Inet6`].
+    /// With [`AddressFamily::Unspec`], the rules of every family are listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure.
+    pub fn list_rules_for(&mut self, family: AddressFamily) -> Result<Vec<Rule>> {
+        self.execute(&ListRules::new(family))
+    }
+
+    /// Install a rule. Like `ip rule add`, a rule without an action looks up
+    /// its table, and the main table if it has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure, and
+    /// [`OperationErrorKind::Exists`] if an identical rule exists.
+    ///
+    /// [`OperationErrorKind::Exists`]: crate::route::OperationErrorKind::Exists
+    pub fn add_rule(&mut self, rule: &Rule) -> Result<()> {
+        self.execute(&AddRule { rule: rule.clone() })?;
+        Ok(())
+    }
+
+    /// Remove the first rule that matches every field that is set, like
+    /// `ip rule del`. A rule listed by [`NetlinkStream::list_rules`] only
+    /// matches itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`crate::Error`] on failure, and
+    /// [`OperationErrorKind::NotFound`] if no rule matches.
+    ///
+    /// [`OperationErrorKind::NotFound`]: crate::route::OperationErrorKind::NotFound
+    pub fn delete_rule(&mut self, rule: &Rule) -> Result<()> {
+        self.execute(&DeleteRule { rule: rule.clone() })?;
+        Ok(())
+    }
+}
+
+/// Dump the policy routing rules. See [`NetlinkStream::list_rules`].
+#[derive(Clone, Debug)]
+pub struct ListRules {
+    /// Address family of the rules
+    pub family: AddressFamily,
+}
+
+impl ListRules {
+    #[must_use]
+    pub fn new(family: AddressFamily) -> Self {
+        Self { family }
+    }
+}
+
+impl Default for ListRules {
+    fn default() -> Self {
+        Self::new(AddressFamily::Inet)
+    }
+}
+
+impl NetlinkRequest for ListRules {
+    type Flags = MessageFlags<GetFlags>;
+    type Response = Rule;
+
+    fn message_type(&self) -> u16 {
+        RouteMessageType::GetRule.into()
+    }
+
+    fn flags(&self) -> Self::Flags {
+        Flags::REQUEST | GetFlags::DUMP
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        // With strict checking, the kernel rejects dumps with anything but
+        // the family set
+        let frh = RuleMessage::builder().family(self.family.into()).build()?;
+        serialize_aligned(frh)
+    }
+
+    fn deserialize(&self, msg: &NetlinkMessage) -> Result<Rule> {
+        parse_rule(&msg.payload)
+    }
+
+    fn map_error(&self, errno: Errno) -> Error {
+        let context = RequestContext::new(RouteMessageType::GetRule);
+        OperationError::new(context, errno).into()
+    }
+}
+
+/// Install a rule. See [`NetlinkStream::add_rule`].
+#[derive(Clone, Debug)]
+pub struct AddRule {
+    pub rule: Rule,
+}
+
+impl NetlinkRequest for AddRule {
+    type Flags = MessageFlags<NewFlags>;
+    type Response = ();
+
+    fn message_type(&self) -> u16 {
+        RouteMessageType::NewRule.into()
+    }
+
+    fn flags(&self) -> Self::Flags {
+        MessageFlags::new(
+            Flags::REQUEST | Flags::ACK,
+            NewFlags::CREATE | NewFlags::EXCL,
+        )
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        let mut rule = self.rule.clone();
+        if rule.action == RuleAction::Unspec {
+            rule.action = if rule.goto.is_some() {
+                RuleAction::Goto
+            } else {
+                RuleAction::ToTable
+            };
+        }
+        if rule.action == RuleAction::ToTable && rule.table == RouteTable::Unspec {
+            rule.table = RouteTable::Main;
+        }
+
+        serialize_rule(&rule)
+    }
+
+    fn deserialize(&self, _msg: &NetlinkMessage) -> Result<()> {
+        Ok(())
+    }
+
+    fn map_error(&self, errno: Errno) -> Error {
+        let context = rule_context(RouteMessageType::NewRule, &self.rule);
+        OperationError::new(context, errno).into()
+    }
+}
+
+/// Remove a rule. See [`NetlinkStream::delete_rule`].
+#[derive(Clone, Debug)]
+pub struct DeleteRule {
+    pub rule: Rule,
+}
+
+impl NetlinkRequest for DeleteRule {
+    type Flags = MessageFlags<DeleteFlags>;
+    type Response = ();
+
+    fn message_type(&self) -> u16 {
+        RouteMessageType::DelRule.into()
+    }
+
+    fn flags(&self) -> Self::Flags {
+        MessageFlags::new(Flags::REQUEST | Flags::ACK, DeleteFlags::empty())
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        // The kernel only compares the fields that are set
+        serialize_rule(&self.rule)
+    }
+
+    fn deserialize(&self, _msg: &NetlinkMessage) -> Result<()> {
+        Ok(())
+    }
+
+    fn map_error(&self, errno: Errno) -> Error {
+        let context = rule_context(RouteMessageType::DelRule, &self.rule);
+        OperationError::new(context, errno).into()
+    }
+}
+
+fn rule_context(typ: RouteMessageType, rule: &Rule) -> RequestContext {
+    let mut context = RequestContext::new(typ);
+    if let Some(prefix) = rule.source.or(rule.dest) {
+        context = context.prefix(prefix.addr(), prefix.prefix_len());
+    }
+    context
+}
+
+/// Parse a `fib_rule_hdr` header and its attributes into a [`Rule`].
+pub(crate) fn parse_rule(payload: &[u8]) -> Result<Rule> {
+    let mut reader = SliceReader::new(payload);
+    let frh = reader.read::<RuleMessage>()?;
+
+    // The kernel leaves out the priority of the rules with priority 0, like
+    // the one that looks up the local table
+    let mut rule = Rule {
+        family: AddressFamily::try_from(frh.family).unwrap_or_default(),
+        priority: Some(0),
+        action: frh.action.into(),
+        table: u32::from(frh.table).into(),
+        tos: frh.tos,
+        invert: frh.flags & FIB_RULE_INVERT != 0,
+        ..Default::default()
+    };
+
+    while !reader.is_empty() {
+        let (typ, value_bytes) = reader.read_attribute()?;
+
+        // Skip attributes added by kernels newer than this library
+        let Some(typ) = deserialize_repr::<RuleAttrType>(typ) else {
+            continue;
+        };
+
+        match RuleAttrValue::deserialize(typ, value_bytes)? {
+            RuleAttrValue::Dest(addr) => rule.dest = Some(IpPrefix::new(addr, frh.dst_len)?),
+            RuleAttrValue::Source(addr) => rule.source = Some(IpPrefix::new(addr, frh.src_len)?),
+            RuleAttrValue::InputInterfaceName(name) => rule.input_interface = Some(name),
+            RuleAttrValue::OutputInterfaceName(name) => rule.output_interface = Some(name),
+            RuleAttrValue::Goto(priority) => rule.goto = Some(priority),
+            RuleAttrValue::Priority(priority) => rule.priority = Some(priority),
+            RuleAttrValue::FwMark(mark) => rule.fwmark = Some(mark),
+            RuleAttrValue::FwMask(mask) => rule.fwmask = Some(mask),
+            RuleAttrValue::Table(table) => rule.table = table.into(),
+            // The kernel reports -1 when they aren't set
+            RuleAttrValue::SuppressPrefixlen(len) => {
+                rule.suppress_prefixlen = (len != u32::MAX).then_some(len);
+            }
+            RuleAttrValue::SuppressIfgroup(group) => {
+                rule.suppress_ifgroup = (group != u32::MAX).then_some(group);
+            }
+            RuleAttrValue::UidRange(range) => rule.uid_range = Some(range),
+            RuleAttrValue::Protocol(protocol) => rule.protocol = protocol.into(),
+            RuleAttrValue::IpProto(proto) => rule.ip_proto = Some(proto),
+            RuleAttrValue::SourcePortRange(range) => rule.source_port_range = Some(range),
+            RuleAttrValue::DestPortRange(range) => rule.dest_port_range = Some(range),
+            _ => {}
+        }
+    }
+
+    Ok(rule)
+}
+
+pub(crate) fn rule_family(rule: &Rule) -> AddressFamily {
+    let prefix = rule.source.or(rule.dest);
+    match (rule.family, prefix) {
+        (AddressFamily::Unspec, Some(prefix)) => prefix.family(),
+        (AddressFamily::Unspec, None) => AddressFamily::Inet,
+        (family, _) => family,
+    }
+}
+
+/// Serialize a [`Rule`] into a `fib_rule_hdr` header followed by its
+/// attributes, the same way the kernel describes it in response to
+/// [`RouteMessageType::GetRule`].
+pub(crate) fn serialize_rule(rule: &Rule) -> Result<Vec<u8>> {
+    let frh = RuleMessage::builder()
+        .family(rule_family(rule).into())
+        .dst_len(rule.dest.map_or(0, |prefix| prefix.prefix_len()))
+        .src_len(rule.source.map_or(0, |prefix| prefix.prefix_len()))
+        .tos(rule.tos)
+        .table(header_table(rule.table))
+        .action(rule.action.into())
+        .flags(if rule.invert { FIB_RULE_INVERT } else { 0 })
+        .build()?;
+
+    let mut bytes = serialize_aligned(frh)?;
+    for attr in rule_attributes(rule) {
+        let (typ, value) = attr.serialize();
+        bytes.append(&mut serialize_attribute(typ.into(), &value)?);
+    }
+
+    Ok(bytes)
+}
+
+fn rule_attributes(rule: &Rule) -> Vec<RuleAttrValue> {
+    let mut attrs = vec![];
+    if let Some(priority) = rule.priority {
+        attrs.push(RuleAttrValue::Priority(priority));
+    }
+    if rule.table != RouteTable::Unspec {
+        attrs.push(RuleAttrValue::Table(rule.table.into()));
+    }
+    if let Some(prefix) = rule.source {
+        attrs.push(RuleAttrValue::Source(prefix.addr()));
+    }
+    if let Some(prefix) = rule.dest {
+        attrs.push(RuleAttrValue::Dest(prefix.addr()));
+    }
+    if let Some(mark) = rule.fwmark {
+        attrs.push(RuleAttrValue::FwMark(mark));
+    }
+    if let Some(mask) = rule.fwmask {
+        attrs.push(RuleAttrValue::FwMask(mask));
+    }
+    if let Some(name) = &rule.input_interface {
+        attrs.push(RuleAttrValue::InputInterfaceName(name.clone()));
+    }
+    if let Some(name) = &rule.output_interface {
+        attrs.push(RuleAttrValue::OutputInterfaceName(name.clone()));
+    }
+    if let Some(priority) = rule.goto {
+        attrs.push(RuleAttrValue::Goto(priority));
+    }
+    if let Some(len) = rule.suppress_prefixlen {
+        attrs.push(RuleAttrValue::SuppressPrefixlen(len));
+    }
+    if let Some(group) = rule.suppress_ifgroup {
+        attrs.push(RuleAttrValue::SuppressIfgroup(group));
+    }
+    if let Some(range) = &rule.uid_range {
+        attrs.push(RuleAttrValue::UidRange(range.clone()));
+    }
+    if rule.protocol != RouteProtocol::Unspec {
+        attrs.push(RuleAttrValue::Protocol(rule.protocol.into()));
+    }
+    if let Some(proto) = rule.ip_proto {
+        attrs.push(RuleAttrValue::IpProto(proto));
+    }
+    if let Some(range) = &rule.source_port_range {
+        attrs.push(RuleAttrValue::SourcePortRange(range.clone()));
+    }
+    if let Some(range) = &rule.dest_port_range {
+        attrs.push(RuleAttrValue::DestPortRange(range.clone()));
+    }
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RTM_NEWRULE payload of `ip rule add priority 1000 from 10.10.0.0/24 to
+    // 198.51.100.0/24 iif nltest0 fwmark 0x10/0xff ipproto tcp sport
+    // 1000-2000 dport 443 uidrange 100-200 lookup 100`, as dumped by the
+    // kernel. 29 is newer than this library.
+    #[rustfmt::skip]
+    const KERNEL_RULE: &[u8] = &[
+        0x02, 0x18, 0x18, 0x00, 0x64, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0x64, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0e, 0x00,
+        0xff, 0xff, 0xff, 0xff, 0x05, 0x00, 0x15, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x03, 0x00,
+        0x6e, 0x6c, 0x74, 0x65, 0x73, 0x74, 0x30, 0x00,
+        0x08, 0x00, 0x06, 0x00, 0xe8, 0x03, 0x00, 0x00,
+        0x08, 0x00, 0x0a, 0x00, 0x10, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x10, 0x00, 0xff, 0x00, 0x00, 0x00,
+        0x0c, 0x00, 0x14, 0x00, 0x64, 0x00, 0x00, 0x00,
+        0xc8, 0x00, 0x00, 0x00, 0x08, 0x00, 0x17, 0x00,
+        0xe8, 0x03, 0xd0, 0x07, 0x08, 0x00, 0x18, 0x00,
+        0xbb, 0x01, 0xbb, 0x01, 0x06, 0x00, 0x1d, 0x00,
+        0xff, 0xff, 0x00, 0x00, 0x05, 0x00, 0x16, 0x00,
+        0x06, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00,
+        0xc6, 0x33, 0x64, 0x00, 0x08, 0x00, 0x02, 0x00,
+        0x0a, 0x0a, 0x00, 0x00,
+    ];
+
+    // `ip rule add priority 1001 lookup main suppress_prefixlength 0`
+    #[rustfmt::skip]
+    const KERNEL_SUPPRESS_RULE: &[u8] = &[
+        0x02, 0x00, 0x00, 0x00, 0xfe, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0xfe, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0e, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x15, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x06, 0x00,
+        0xe9, 0x03, 0x00, 0x00,
+    ];
+
+    // `ip rule add priority 1002 not from all lookup 200`
+    #[rustfmt::skip]
+    const KERNEL_INVERT_RULE: &[u8] = &[
+        0x02, 0x00, 0x00, 0x00, 0xc8, 0x00, 0x00, 0x01,
+        0x02, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0f, 0x00,
+        0xc8, 0x00, 0x00, 0x00, 0x08, 0x00, 0x0e, 0x00,
+        0xff, 0xff, 0xff, 0xff, 0x05, 0x00, 0x15, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x06, 0x00,
+        0xea, 0x03, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn kernel_rule_is_parsed() -> Result<()> {
+        let expected = Rule::builder()
+            .family(AddressFamily::Inet)
+            .priority(1000u32)
+            .action(RuleAction::ToTable)
+            .table(RouteTable::from(100))
+            .source("10.10.0.0/24".parse::<IpPrefix>()?)
+            .dest("198.51.100.0/24".parse::<IpPrefix>()?)
+            .input_interface("nltest0")
+            .fwmark(0x10u32)
+            .fwmask(0xffu32)
+            .uid_range(100..=200)
+            .ip_proto(6)
+            .source_port_range(1000..=2000)
+            .dest_port_range(443..=443)
+            .build()?;
+        assert_eq!(parse_rule(KERNEL_RULE)?, expected);
+        Ok(())
+    }
+
+    // The kernel reports FRA_SUPPRESS_PREFIXLEN as -1 when it isn't set
+    #[test]
+    fn unset_suppress_prefixlen_is_none() -> Result<()> {
+        let rule = parse_rule(KERNEL_INVERT_RULE)?;
+        assert_eq!(rule.suppress_prefixlen, None);
+        assert!(rule.invert);
+        assert_eq!(rule.table, RouteTable::from(200));
+
+        let rule = parse_rule(KERNEL_SUPPRESS_RULE)?;
+        assert_eq!(rule.suppress_prefixlen, Some(0));
+        assert_eq!(rule.table, RouteTable::Main);
+        Ok(())
+    }
+
+    #[test]
+    fn rule_round_trips() -> Result<()> {
+        let rule = Rule::builder()
+            .family(AddressFamily::Inet6)
+            .priority(2000u32)
+            .action(RuleAction::Goto)
+            .goto(3000u32)
+            .table(RouteTable::from(1000))
+            .source("2001:db8::/32".parse::<IpPrefix>()?)
+            .dest("2001:db8:1::/48".parse::<IpPrefix>()?)
+            .tos(0x10)
+            .invert(true)
+            .fwmark(1u32)
+            .input_interface("eth0")
+            .output_interface("eth1")
+            .suppress_prefixlen(8u32)
+            .suppress_ifgroup(2u32)
+            .uid_range(1000..=u32::MAX - 1)
+            .ip_proto(17)
+            .source_port_range(0..=u16::MAX)
+            .dest_port_range(53..=53)
+            .protocol(RouteProtocol::Static)
+            .build()?;
+        assert_eq!(parse_rule(&serialize_rule(&rule)?)?, rule);
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+mod client;
+pub use client::*;
+
+pub mod types;
+pub use types::*;
+
+mod pretty;
+pub use pretty::*;
@@ -0,0 +1,75 @@
+use super::RuleMessage;
+use crate::bytes::{deserialize_repr, SliceReader};
+use crate::pretty::{decode_attributes, family_name, AttrKind, AttrSpec, Field, FieldValue};
+use crate::pretty::{FlagKind, MessageDecoder};
+use crate::route::route::RouteMessageType;
+use crate::Result;
+
+/// Decodes `RTM_*RULE` messages. See [`crate::DecoderRegistry`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RuleDecoder;
+
+#[rustfmt::skip]
+const FRA_ATTRS: &[AttrSpec] = &[
+    AttrSpec::new(1, "FRA_DST", AttrKind::Addr),
+    AttrSpec::new(2, "FRA_SRC", AttrKind::Addr),
+    AttrSpec::new(3, "FRA_IIFNAME", AttrKind::Str),
+    AttrSpec::new(4, "FRA_GOTO", AttrKind::U32),
+    AttrSpec::new(6, "FRA_PRIORITY", AttrKind::U32),
+    AttrSpec::new(10, "FRA_FWMARK", AttrKind::U32),
+    AttrSpec::new(11, "FRA_FLOW", AttrKind::U32),
+    AttrSpec::new(12, "FRA_TUN_ID", AttrKind::Bytes),
+    AttrSpec::new(13, "FRA_SUPPRESS_IFGROUP", AttrKind::U32),
+    AttrSpec::new(14, "FRA_SUPPRESS_PREFIXLEN", AttrKind::U32),
+    AttrSpec::new(15, "FRA_TABLE", AttrKind::U32),
+    AttrSpec::new(16, "FRA_FWMASK", AttrKind::U32),
+    AttrSpec::new(17, "FRA_OIFNAME", AttrKind::Str),
+    AttrSpec::new(18, "FRA_PAD", AttrKind::Bytes),
+    AttrSpec::new(19, "FRA_L3MDEV", AttrKind::U8),
+    AttrSpec::new(20, "FRA_UID_RANGE", AttrKind::Bytes),
+    AttrSpec::new(21, "FRA_PROTOCOL", AttrKind::U8),
+    AttrSpec::new(22, "FRA_IP_PROTO", AttrKind::U8),
+    AttrSpec::new(23, "FRA_SPORT_RANGE", AttrKind::Bytes),
+    AttrSpec::new(24, "FRA_DPORT_RANGE", AttrKind::Bytes),
+];
+
+impl MessageDecoder for RuleDecoder {
+    fn type_name(&self, typ: u16) -> Option<&'static str> {
+        match deserialize_repr::<RouteMessageType>(typ)? {
+            RouteMessageType::NewRule => Some("RTM_NEWRULE"),
+            RouteMessageType::DelRule => Some("RTM_DELRULE"),
+            RouteMessageType::GetRule => Some("RTM_GETRULE"),
+            _ => None,
+        }
+    }
+
+    fn flag_kind(&self, typ: u16) -> FlagKind {
+        match deserialize_repr::<RouteMessageType>(typ) {
+            Some(RouteMessageType::NewRule) => FlagKind::New,
+            Some(RouteMessageType::DelRule) => FlagKind::Delete,
+            Some(RouteMessageType::GetRule) => FlagKind::Get,
+            _ => FlagKind::None,
+        }
+    }
+
+    fn decode(&self, _typ: u16, payload: &[u8]) -> Result<Vec<FieldValue>> {
+        let mut reader = SliceReader::new(payload);
+        let frh = reader.read::<RuleMessage>()?;
+
+        let header = vec![
+            Field::text("family", &family_name(frh.family)),
+            Field::text("dst_len", &frh.dst_len),
+            Field::text("src_len", &frh.src_len),
+            Field::text("tos", &frh.tos),
+            Field::text("table", &frh.table),
+            Field::text("action", &frh.action),
+            Field::text("flags", &format!("{:#x}", frh.flags)),
+        ];
+
+        let attrs = decode_attributes(reader.take(reader.remaining())?, FRA_ATTRS)?;
+        Ok(vec![
+            FieldValue::Struct(header),
+            FieldValue::Attributes(attrs),
+        ])
+    }
+}
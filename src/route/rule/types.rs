@@ -0,0 +1,302 @@
+use crate::bytes::{deserialize_ascii, deserialize_ip_addr, deserialize_u32, deserialize_u8};
+use crate::bytes::{deserialize_u16, deserialize_u64_be, serialize_ascii, serialize_ip_addr};
+use crate::route::route::impl_raw_enum;
+use crate::{Error, Result};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::fmt;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+/// The rule matches the packets that don't match its selectors, like `not`
+/// in `ip rule`
+pub const FIB_RULE_INVERT: u32 = 0x2;
+/// The rule is a goto whose target doesn't exist
+pub const FIB_RULE_UNRESOLVED: u32 = 0x4;
+
+/// Header for messages that create, delete or receive information about a
+/// policy routing rule.
+///
+/// See `struct fib_rule_hdr` in
+/// [`fib_rules.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/fib_rules.h).
+#[repr(C)]
+#[derive(PartialEq, Clone, Debug, Default, Builder, Serialize, Deserialize)]
+#[builder(default, build_fn(error = "Error"))]
+pub struct RuleMessage {
+    pub family: u8,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    pub table: u8,
+    pub res1: u8,
+    pub res2: u8,
+    /// See [`RuleAction`]
+    pub action: u8,
+    /// `FIB_RULE_*` flags, like [`FIB_RULE_INVERT`]
+    pub flags: u32,
+}
+
+impl RuleMessage {
+    #[must_use]
+    pub fn builder() -> RuleMessageBuilder {
+        RuleMessageBuilder::default()
+    }
+}
+
+/// What a rule does with the packets it matches, like `lookup` or
+/// `prohibit` in `ip rule`.
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub enum RuleAction {
+    /// No action, which [`crate::NetlinkStream::add_rule`] replaces with
+    /// [`RuleAction::ToTable`]
+    #[default]
+    Unspec,
+    /// Look up the route in the rule's table
+    ToTable,
+    /// Jump to the rule with the priority in [`crate::route::rule::Rule::goto`]
+    Goto,
+    /// Do nothing, and move on to the next rule
+    Nop,
+    Blackhole,
+    Unreachable,
+    Prohibit,
+    Other(u8),
+}
+
+#[rustfmt::skip]
+impl_raw_enum!(RuleAction, u8, Other, {
+    Unspec = 0 => "unspec",
+    ToTable = 1 => "lookup",
+    Goto = 2 => "goto",
+    Nop = 3 => "nop",
+    Blackhole = 6 => "blackhole",
+    Unreachable = 7 => "unreachable",
+    Prohibit = 8 => "prohibit",
+});
+
+/// Type of the rule attribute. See `FRA_*` in
+/// [`fib_rules.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/fib_rules.h).
+#[repr(u16)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize_repr, Deserialize_repr)]
+pub enum RuleAttrType {
+    Unspec = 0,
+    Dest = 1,
+    Source = 2,
+    InputInterfaceName = 3,
+    Goto = 4,
+    Priority = 6,
+    FwMark = 10,
+    Flow = 11,
+    TunId = 12,
+    SuppressIfgroup = 13,
+    SuppressPrefixlen = 14,
+    Table = 15,
+    FwMask = 16,
+    OutputInterfaceName = 17,
+    Pad = 18,
+    L3mdev = 19,
+    UidRange = 20,
+    Protocol = 21,
+    IpProto = 22,
+    SourcePortRange = 23,
+    DestPortRange = 24,
+}
+
+impl From<RuleAttrType> for u16 {
+    fn from(typ: RuleAttrType) -> Self {
+        typ as u16
+    }
+}
+
+/// Strongly-typed rule attribute.
+#[derive(PartialEq, Clone, Debug)]
+pub enum RuleAttrValue {
+    Unspec,
+    Dest(IpAddr),
+    Source(IpAddr),
+    InputInterfaceName(String),
+    /// Priority of the rule to jump to
+    Goto(u32),
+    Priority(u32),
+    FwMark(u32),
+    /// Realms, like `realms` in `ip rule`
+    Flow(u32),
+    /// In host byte order, though it's sent in network byte order
+    TunId(u64),
+    SuppressIfgroup(u32),
+    SuppressPrefixlen(u32),
+    Table(u32),
+    FwMask(u32),
+    OutputInterfaceName(String),
+    Pad,
+    /// Look up the table of the VRF the packet is in
+    L3mdev(u8),
+    UidRange(RangeInclusive<u32>),
+    /// See [`crate::route::route::RouteProtocol`]
+    Protocol(u8),
+    IpProto(u8),
+    SourcePortRange(RangeInclusive<u16>),
+    DestPortRange(RangeInclusive<u16>),
+}
+
+// Ranges are a pair of start and end, in host byte order. See
+// `struct fib_rule_uid_range` and `struct fib_rule_port_range`.
+fn deserialize_range<T>(
+    payload: &[u8],
+    deserialize: fn(&[u8]) -> Result<T>,
+) -> Result<RangeInclusive<T>> {
+    let (start, end) = payload.split_at(payload.len() / 2);
+    Ok(deserialize(start)?..=deserialize(end)?)
+}
+
+fn serialize_range<T: Copy, const N: usize>(
+    range: &RangeInclusive<T>,
+    to_bytes: fn(T) -> [u8; N],
+) -> Vec<u8> {
+    let mut bytes = to_bytes(*range.start()).to_vec();
+    bytes.extend_from_slice(&to_bytes(*range.end()));
+    bytes
+}
+
+#[rustfmt::skip]
+impl RuleAttrValue {
+    pub(crate) fn deserialize(typ: RuleAttrType, payload: &[u8]) -> Result<Self> {
+        match typ {
+            RuleAttrType::Unspec => {
+                Ok(Self::Unspec)
+            }
+            RuleAttrType::Dest => {
+                deserialize_ip_addr(payload).map(Self::Dest)
+            }
+            RuleAttrType::Source => {
+                deserialize_ip_addr(payload).map(Self::Source)
+            }
+            RuleAttrType::InputInterfaceName => {
+                Ok(Self::InputInterfaceName(deserialize_ascii(payload)))
+            }
+            RuleAttrType::Goto => {
+                deserialize_u32(payload).map(Self::Goto)
+            }
+            RuleAttrType::Priority => {
+                deserialize_u32(payload).map(Self::Priority)
+            }
+            RuleAttrType::FwMark => {
+                deserialize_u32(payload).map(Self::FwMark)
+            }
+            RuleAttrType::Flow => {
+                deserialize_u32(payload).map(Self::Flow)
+            }
+            RuleAttrType::TunId => {
+                deserialize_u64_be(payload).map(Self::TunId)
+            }
+            RuleAttrType::SuppressIfgroup => {
+                deserialize_u32(payload).map(Self::SuppressIfgroup)
+            }
+            RuleAttrType::SuppressPrefixlen => {
+                deserialize_u32(payload).map(Self::SuppressPrefixlen)
+            }
+            RuleAttrType::Table => {
+                deserialize_u32(payload).map(Self::Table)
+            }
+            RuleAttrType::FwMask => {
+                deserialize_u32(payload).map(Self::FwMask)
+            }
+            RuleAttrType::OutputInterfaceName => {
+                Ok(Self::OutputInterfaceName(deserialize_ascii(payload)))
+            }
+            RuleAttrType::Pad => {
+                Ok(Self::Pad)
+            }
+            RuleAttrType::L3mdev => {
+                deserialize_u8(payload).map(Self::L3mdev)
+            }
+            RuleAttrType::UidRange => {
+                deserialize_range(payload, deserialize_u32).map(Self::UidRange)
+            }
+            RuleAttrType::Protocol => {
+                deserialize_u8(payload).map(Self::Protocol)
+            }
+            RuleAttrType::IpProto => {
+                deserialize_u8(payload).map(Self::IpProto)
+            }
+            RuleAttrType::SourcePortRange => {
+                deserialize_range(payload, deserialize_u16).map(Self::SourcePortRange)
+            }
+            RuleAttrType::DestPortRange => {
+                deserialize_range(payload, deserialize_u16).map(Self::DestPortRange)
+            }
+        }
+    }
+}
+
+#[rustfmt::skip]
+impl RuleAttrValue {
+    pub(crate) fn serialize(&self) -> (RuleAttrType, Vec<u8>) {
+        match self {
+            Self::Unspec => {
+                (RuleAttrType::Unspec, vec![])
+            }
+            Self::Dest(addr) => {
+                (RuleAttrType::Dest, serialize_ip_addr(addr))
+            }
+            Self::Source(addr) => {
+                (RuleAttrType::Source, serialize_ip_addr(addr))
+            }
+            Self::InputInterfaceName(name) => {
+                (RuleAttrType::InputInterfaceName, serialize_ascii(name))
+            }
+            Self::Goto(value) => {
+                (RuleAttrType::Goto, value.to_le_bytes().to_vec())
+            }
+            Self::Priority(value) => {
+                (RuleAttrType::Priority, value.to_le_bytes().to_vec())
+            }
+            Self::FwMark(value) => {
+                (RuleAttrType::FwMark, value.to_le_bytes().to_vec())
+            }
+            Self::Flow(value) => {
+                (RuleAttrType::Flow, value.to_le_bytes().to_vec())
+            }
+            Self::TunId(value) => {
+                (RuleAttrType::TunId, value.to_be_bytes().to_vec())
+            }
+            Self::SuppressIfgroup(value) => {
+                (RuleAttrType::SuppressIfgroup, value.to_le_bytes().to_vec())
+            }
+            Self::SuppressPrefixlen(value) => {
+                (RuleAttrType::SuppressPrefixlen, value.to_le_bytes().to_vec())
+            }
+            Self::Table(value) => {
+                (RuleAttrType::Table, value.to_le_bytes().to_vec())
+            }
+            Self::FwMask(value) => {
+                (RuleAttrType::FwMask, value.to_le_bytes().to_vec())
+            }
+            Self::OutputInterfaceName(name) => {
+                (RuleAttrType::OutputInterfaceName, serialize_ascii(name))
+            }
+            Self::Pad => {
+                (RuleAttrType::Pad, vec![])
+            }
+            Self::L3mdev(value) => {
+                (RuleAttrType::L3mdev, vec![*value])
+            }
+            Self::UidRange(range) => {
+                (RuleAttrType::UidRange, serialize_range(range, u32::to_le_bytes))
+            }
+            Self::Protocol(value) => {
+                (RuleAttrType::Protocol, vec![*value])
+            }
+            Self::IpProto(value) => {
+                (RuleAttrType::IpProto, vec![*value])
+            }
+            Self::SourcePortRange(range) => {
+                (RuleAttrType::SourcePortRange, serialize_range(range, u16::to_le_bytes))
+            }
+            Self::DestPortRange(range) => {
+                (RuleAttrType::DestPortRange, serialize_range(range, u16::to_le_bytes))
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use crate::bytes::{deserialize_repr, serialize_aligned};
+use crate::codec::{serialize_message, split_messages};
+use crate::flags::{Flags, GetFlags, NewFlags};
+use crate::route::link::{serialize_link, Link};
+use crate::route::route::RouteProtocol;
+use crate::route::route::{parse_route, route_family, serialize_route, Route, RouteMessageType};
+use crate::route::AddressFamily;
+use crate::transport::Transport;
+use crate::types::{MessageType, NetlinkHeader, NetlinkMessage};
+use crate::{Error, Result};
+use nix::errno::Errno;
+use std::collections::{HashMap, VecDeque};
+
+/// In-memory stand-in for the kernel's `NETLINK_ROUTE` implementation. This
+/// makes it possible to test code that uses a [`crate::NetlinkStream`]
+/// without a real socket or any special privileges.
+///
+/// It answers [`RouteMessageType::GetLink`] and [`RouteMessageType::GetRoute`]
+/// dumps from the canned [`Link`]s and [`Route`]s it was given, adds and
+/// deletes routes, and records every request it receives. Requests it does
+/// not understand are answered with `EOPNOTSUPP`, like a kernel without
+/// support for them would.
+#[derive(Clone, Debug, Default)]
+pub struct FakeKernel {
+    links: Vec<Link>,
+    routes: Vec<Route>,
+    errors: HashMap<u16, i32>,
+    requests: Vec<NetlinkMessage>,
+    datagrams: VecDeque<Vec<u8>>,
+}
+
+impl FakeKernel {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include `link` in the responses to link dumps.
+    #[must_use]
+    pub fn with_link(mut self, link: Link) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    /// Include `route` in the responses to route dumps.
+    #[must_use]
+    pub fn with_route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Answer every request with the given message type with `errno`.
+    #[must_use]
+    pub fn with_error<I: Into<u16>>(self, typ: I, errno: Errno) -> Self {
+        self.with_error_code(typ, -(errno as i32))
+    }
+
+    /// Answer every request with the given message type with an error
+    /// message carrying `code`. The kernel sends negative errnos, so this is
+    /// for testing how malformed or corrupted errors are handled.
+    #[must_use]
+    pub fn with_error_code<I: Into<u16>>(mut self, typ: I, code: i32) -> Self {
+        self.errors.insert(typ.into(), code);
+        self
+    }
+
+    /// Every request that has been sent to the kernel, in order.
+    #[must_use]
+    pub fn requests(&self) -> &[NetlinkMessage] {
+        &self.requests
+    }
+
+    fn handle(&mut self, req: &NetlinkHeader, payload: &[u8]) -> Result<Vec<u8>> {
+        if let Some(code) = self.errors.get(&req.typ) {
+            return serialize_error_code(req, *code);
+        }
+
+        match deserialize_repr::<RouteMessageType>(req.typ) {
+            Some(RouteMessageType::NewRoute) => return self.add_route(req, payload),
+            Some(RouteMessageType::DelRoute) => return self.delete_route(req, payload),
+            _ => {}
+        }
+
+        let is_dump = req.has_flags(GetFlags::DUMP);
+        let (typ, objects) = match deserialize_repr::<RouteMessageType>(req.typ) {
+            Some(RouteMessageType::GetLink) if is_dump => {
+                let links = self.links.iter().map(serialize_link);
+                (
+                    RouteMessageType::NewLink,
+                    links.collect::<Result<Vec<_>>>()?,
+                )
+            }
+            Some(RouteMessageType::GetRoute) if is_dump => {
+                // Like the kernel, only dump the routes of the requested family
+                let family = payload.first().copied().unwrap_or_default();
+                let family = AddressFamily::try_from(family).unwrap_or_default();
+                let routes = self.routes.iter().filter(|route| {
+                    family == AddressFamily::Unspec || family == route_family(route)
+                });
+                let routes = routes.map(serialize_route);
+                (
+                    RouteMessageType::NewRoute,
+                    routes.collect::<Result<Vec<_>>>()?,
+                )
+            }
+            _ => {
+                return serialize_error(req, Errno::EOPNOTSUPP);
+            }
+        };
+
+        let mut datagram = vec![];
+        for payload in objects {
+            let flags = Flags::MULTI.bits();
+            let mut bytes = serialize_message(typ.into(), flags, req.seq, req.pid, payload)?;
+            datagram.append(&mut bytes);
+        }
+
+        // The kernel includes the status of the dump in the done message
+        let flags = Flags::MULTI.bits();
+        let status = 0i32.to_le_bytes().to_vec();
+        let typ = MessageType::Done.into();
+        let mut bytes = serialize_message(typ, flags, req.seq, req.pid, status)?;
+        datagram.append(&mut bytes);
+
+        Ok(datagram)
+    }
+
+    // Routes are keyed by their destination and priority within a table
+    fn add_route(&mut self, req: &NetlinkHeader, payload: &[u8]) -> Result<Vec<u8>> {
+        // Like the kernel, reject requests that can't be parsed
+        let Ok(route) = parse_route(payload) else {
+            return serialize_error(req, Errno::EINVAL);
+        };
+        let existing = self.routes.iter().position(|other| {
+            route_family(other) == route_family(&route)
+                && other.table == route.table
+                && other.dest == route.dest
+                && other.label == route.label
+                && other.priority.unwrap_or_default() == route.priority.unwrap_or_default()
+        });
+
+        match existing {
+            Some(index) if req.has_flags(NewFlags::REPLACE) => self.routes[index] = route,
+            Some(_) => return serialize_error(req, Errno::EEXIST),
+            None if !req.has_flags(NewFlags::CREATE) => {
+                return serialize_error(req, Errno::ENOENT);
+            }
+            None => self.routes.push(route),
+        }
+
+        serialize_ack(req)
+    }
+
+    // Like the kernel, fields left out of the request match any route
+    fn delete_route(&mut self, req: &NetlinkHeader, payload: &[u8]) -> Result<Vec<u8>> {
+        let Ok(route) = parse_route(payload) else {
+            return serialize_error(req, Errno::EINVAL);
+        };
+        let existing = self.routes.iter().position(|other| {
+            route_family(other) == route_family(&route)
+                && other.table == route.table
+                && other.dest == route.dest
+                && other.label == route.label
+                && (route.gateway.is_none() || other.gateway == route.gateway)
+                && (route.priority.is_none() || other.priority == route.priority)
+                && (route.protocol == RouteProtocol::Unspec || other.protocol == route.protocol)
+                && (route.output_interface_index.is_none()
+                    || other.output_interface_index == route.output_interface_index)
+        });
+
+        let Some(index) = existing else {
+            return serialize_error(req, Errno::ESRCH);
+        };
+        self.routes.remove(index);
+
+        serialize_ack(req)
+    }
+}
+
+impl Transport for FakeKernel {
+    fn pid(&self) -> u32 {
+        0
+    }
+
+    fn send(&mut self, buf: &[u8]) -> Result<()> {
+        for (hdr, payload) in split_messages(buf)? {
+            let datagram = self.handle(&hdr, &payload)?;
+            if !datagram.is_empty() {
+                self.datagrams.push_back(datagram);
+            }
+            self.requests
+                .push(NetlinkMessage::new(hdr.into_descriptor(), payload));
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>> {
+        // Behave like a non-blocking socket with nothing left to read
+        self.datagrams
+            .pop_front()
+            .ok_or(Error::ErrRecvSocket(Errno::EAGAIN))
+    }
+}
+
+// Successful requests are only acknowledged if they asked for it
+fn serialize_ack(req: &NetlinkHeader) -> Result<Vec<u8>> {
+    if !req.has_flags(Flags::ACK) {
+        return Ok(vec![]);
+    }
+
+    let mut payload = 0i32.to_le_bytes().to_vec();
+    payload.append(&mut serialize_aligned(req.clone())?);
+    serialize_message(MessageType::Error.into(), 0, req.seq, req.pid, payload)
+}
+
+// Error messages contain the negative errno, followed by the header of the
+// request that caused it.
+fn serialize_error(req: &NetlinkHeader, errno: Errno) -> Result<Vec<u8>> {
+    serialize_error_code(req, -(errno as i32))
+}
+
+fn serialize_error_code(req: &NetlinkHeader, code: i32) -> Result<Vec<u8>> {
+    let mut payload = code.to_le_bytes().to_vec();
+    payload.append(&mut serialize_aligned(req.clone())?);
+    serialize_message(MessageType::Error.into(), 0, req.seq, req.pid, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::route::RouteTable;
+    use crate::route::{IpPrefix, OperationErrorKind};
+    use crate::NetlinkStream;
+    use std::net::IpAddr;
+
+    // Send a request and read the errno of the response
+    fn request_errno(
+        kernel: &mut FakeKernel,
+        typ: RouteMessageType,
+        payload: Vec<u8>,
+    ) -> Result<i32> {
+        let flags = (Flags::REQUEST | Flags::ACK).bits();
+        kernel.send(&serialize_message(typ.into(), flags, 1, 0, payload)?)?;
+
+        let datagram = kernel.recv()?;
+        let (hdr, payload) = split_messages(&datagram)?.remove(0);
+        assert!(hdr.has_type(MessageType::Error));
+        Ok(i32::from_le_bytes(payload[..4].try_into().unwrap()))
+    }
+
+    #[test]
+    fn malformed_route_is_rejected() -> Result<()> {
+        let mut kernel = FakeKernel::new();
+        for typ in [RouteMessageType::NewRoute, RouteMessageType::DelRoute] {
+            let errno = request_errno(&mut kernel, typ, vec![0; 2])?;
+            assert_eq!(errno, -(Errno::EINVAL as i32));
+        }
+        assert_eq!(kernel.requests().len(), 2);
+        Ok(())
+    }
+
+    fn route<A: Into<IpAddr>>(dest: &str, gateway: A) -> Result<Route> {
+        Route::builder()
+            .dest(dest.parse::<IpPrefix>()?)
+            .gateway(gateway.into())
+            .build()
+    }
+
+    // The type and flags of every request the kernel received
+    fn requests(conn: &NetlinkStream<FakeKernel>) -> Vec<(u16, u16)> {
+        let requests = conn.get_ref().requests().iter();
+        requests
+            .map(|req| (req.header.typ(), req.header.flags()))
+            .collect()
+    }
+
+    #[test]
+    fn links_are_listed() -> Result<()> {
+        let lo = Link {
+            typ: 772,
+            index: 1,
+            name: Some("lo".to_string()),
+            ..Default::default()
+        };
+        let eth0 = Link {
+            typ: 1,
+            index: 2,
+            name: Some("eth0".to_string()),
+            addr: Some(vec![0x02, 0x00, 0x00, 0x00, 0x00, 0x01]),
+            ..Default::default()
+        };
+        let kernel = FakeKernel::new()
+            .with_link(lo.clone())
+            .with_link(eth0.clone());
+        let mut conn = NetlinkStream::new(kernel);
+
+        assert_eq!(conn.list_links()?, [lo, eth0]);
+
+        let dump = (Flags::REQUEST | GetFlags::DUMP).bits();
+        assert_eq!(requests(&conn), [(RouteMessageType::GetLink.into(), dump)]);
+        Ok(())
+    }
+
+    #[test]
+    fn routes_are_listed_by_family() -> Result<()> {
+        let kernel = FakeKernel::new()
+            .with_route(route("10.0.0.0/8", [192, 0, 2, 1])?)
+            .with_route(route("2001:db8::/32", [0xfe80, 0, 0, 0, 0, 0, 0, 1])?);
+        let mut conn = NetlinkStream::new(kernel);
+
+        let routes = conn.list_routes()?;
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].dest, Some("10.0.0.0/8".parse::<IpPrefix>()?));
+        assert_eq!(routes[0].family, AddressFamily::Inet);
+        assert_eq!(conn.list_routes_for(AddressFamily::Unspec)?.len(), 2);
+
+        // The family is the first byte of the rtmsg
+        let families: Vec<_> = conn
+            .get_ref()
+            .requests()
+            .iter()
+            .map(|req| req.payload[0])
+            .collect();
+        assert_eq!(
+            families,
+            [AddressFamily::Inet.into(), AddressFamily::Unspec.into()]
+        );
+
+        let dump = (Flags::REQUEST | GetFlags::DUMP).bits();
+        let get = RouteMessageType::GetRoute.into();
+        assert_eq!(requests(&conn), [(get, dump), (get, dump)]);
+        Ok(())
+    }
+
+    #[test]
+    fn routes_are_added_replaced_and_deleted() -> Result<()> {
+        let mut conn = NetlinkStream::new(FakeKernel::new());
+
+        conn.add_route(&route("10.0.0.0/8", [192, 0, 2, 1])?)?;
+        let err = conn
+            .add_route(&route("10.0.0.0/8", [192, 0, 2, 2])?)
+            .unwrap_err();
+        assert_eq!(err.operation_kind(), Some(OperationErrorKind::RouteExists));
+
+        conn.replace_route(&route("10.0.0.0/8", [192, 0, 2, 2])?)?;
+        let routes = conn.list_routes()?;
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].gateway, Some(IpAddr::from([192, 0, 2, 2])));
+        assert_eq!(routes[0].table, RouteTable::Main);
+        assert_eq!(routes[0].protocol, RouteProtocol::Boot);
+
+        conn.delete_route(&route("10.0.0.0/8", [192, 0, 2, 2])?)?;
+        let err = conn
+            .delete_route(&route("10.0.0.0/8", [192, 0, 2, 2])?)
+            .unwrap_err();
+        assert_eq!(err.operation_kind(), Some(OperationErrorKind::NoSuchRoute));
+        assert!(conn.list_routes()?.is_empty());
+
+        let new = RouteMessageType::NewRoute.into();
+        let del = RouteMessageType::DelRoute.into();
+        let get = RouteMessageType::GetRoute.into();
+        let add = (Flags::REQUEST | Flags::ACK | NewFlags::CREATE | NewFlags::EXCL).bits();
+        let replace = (Flags::REQUEST | Flags::ACK | NewFlags::CREATE | NewFlags::REPLACE).bits();
+        let delete = (Flags::REQUEST | Flags::ACK).bits();
+        let dump = (Flags::REQUEST | GetFlags::DUMP).bits();
+        assert_eq!(
+            requests(&conn),
+            [
+                (new, add),
+                (new, add),
+                (new, replace),
+                (get, dump),
+                (del, delete),
+                (del, delete),
+                (get, dump),
+            ]
+        );
+        Ok(())
+    }
+}